@@ -0,0 +1,64 @@
+//! Save keys of each game version. Lists are stored as JSON files in `keys/`
+//! directory of this crate and embedded into binary. Share links are decoded
+//! with them, so list for new game version should be added when keys change:
+//! copy generated `save_keys.json` to `keys/<game version>.json`
+
+use std::collections::BTreeSet;
+
+/// Game version and list of its save keys
+const KEY_LISTS: &[(&str, &str)] = &[("1.1.16", include_str!("../keys/1.1.16.json"))];
+
+/// Save keys of game version
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeySet {
+    pub game_version: String,
+    pub keys: BTreeSet<String>,
+}
+
+impl KeySet {
+    pub fn fingerprint(&self) -> u32 {
+        fingerprint(&self.keys)
+    }
+}
+
+/// Load all known key sets, from oldest game version to newest
+pub fn key_sets() -> Vec<KeySet> {
+    KEY_LISTS
+        .iter()
+        .map(|(game_version, keys)| KeySet {
+            game_version: game_version.to_string(),
+            keys: serde_json::from_str(keys).expect("embedded key list should be valid"),
+        })
+        .collect()
+}
+
+/// 32-bit FNV-1a hash of sorted keys, each followed by `\n`
+pub fn fingerprint<'k>(keys: impl IntoIterator<Item = &'k String>) -> u32 {
+    let mut keys: Vec<_> = keys.into_iter().collect();
+    keys.sort();
+    let mut hash: u32 = 0x811c9dc5;
+    for b in keys.into_iter().flat_map(|k| k.bytes().chain([b'\n'])) {
+        hash ^= b as u32;
+        hash = hash.wrapping_mul(0x01000193);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_sets() {
+        let sets = key_sets();
+        assert_eq!(sets[0].game_version, "1.1.16");
+        assert_eq!(sets[0].keys.len(), 374);
+        // changing fingerprint breaks existing links
+        assert_eq!(sets[0].fingerprint(), 0x0a3f27fe);
+
+        let keys = ["B".to_string(), "A".to_string()];
+        assert_eq!(fingerprint(&keys), fingerprint(keys.iter().rev()));
+        // "A\n" only
+        assert_eq!(fingerprint(&keys[1..]), 0x64d579b2);
+    }
+}
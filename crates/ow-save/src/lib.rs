@@ -0,0 +1,177 @@
+//! Models of Outer Wilds save file
+//!
+//! Save is JSON, written by game to `data.owsave`. Only fields, related to
+//! ship log and exploration progress, are modeled, other fields are ignored
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+pub mod keys;
+pub mod link;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SaveError {
+    #[error("reading {path}: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("parsing save: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+/// Game save
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Save {
+    #[serde(default)]
+    pub loop_count: i32,
+
+    /// Known signal frequencies, by index of frequency
+    #[serde(default)]
+    pub known_frequencies: Vec<bool>,
+
+    /// Map of signal name to whether it's known
+    #[serde(default)]
+    pub known_signals: BTreeMap<String, bool>,
+
+    /// Conditions, which are reset on each loop
+    #[serde(default)]
+    pub dict_conditions: BTreeMap<String, bool>,
+
+    /// Conditions, which persist between loops
+    #[serde(default)]
+    pub persistent_conditions: BTreeMap<String, bool>,
+
+    /// Map of fact id to its state
+    pub ship_log_fact_saves: BTreeMap<String, FactSave>,
+}
+
+/// State of ship log fact
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FactSave {
+    #[serde(default)]
+    pub id: String,
+
+    /// Order in which fact was revealed, `-1` if fact is not revealed
+    pub reveal_order: i32,
+
+    /// Fact was shown in ship log
+    #[serde(default)]
+    pub read: bool,
+
+    /// Fact was revealed, but ship log was not opened after that
+    #[serde(default)]
+    pub newly_revealed: bool,
+}
+
+impl FactSave {
+    pub fn is_revealed(&self) -> bool {
+        self.reveal_order >= 0
+    }
+}
+
+/// Difference between known fact keys and keys in save
+#[derive(Debug, Default, PartialEq)]
+pub struct SaveKeysDiff {
+    pub only_in_data: Vec<String>,
+    pub only_in_save: Vec<String>,
+}
+
+impl Save {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, SaveError> {
+        let path = path.as_ref();
+        let data = std::fs::read(path).map_err(|source| SaveError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::from_slice(&data)
+    }
+
+    pub fn from_slice(data: &[u8]) -> Result<Self, SaveError> {
+        Ok(serde_json::from_slice(data)?)
+    }
+
+    /// Ids of revealed facts. `read` and `newlyRevealed` are not considered,
+    /// fact is opened as soon as it has reveal order
+    pub fn opened_facts(&self) -> BTreeSet<&str> {
+        self.ship_log_fact_saves
+            .iter()
+            .filter(|(_, f)| f.is_revealed())
+            .map(|(id, _)| id.as_str())
+            .collect()
+    }
+
+    /// Compare fact keys in save with `keys`, which are ids of facts from
+    /// extracted entries
+    pub fn compare_keys(&self, keys: &BTreeSet<String>) -> SaveKeysDiff {
+        SaveKeysDiff {
+            only_in_data: keys
+                .iter()
+                .filter(|k| !self.ship_log_fact_saves.contains_key(*k))
+                .cloned()
+                .collect(),
+            only_in_save: self
+                .ship_log_fact_saves
+                .keys()
+                .filter(|k| !keys.contains(*k))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAVE: &str = r#"{
+        "loopCount": 12,
+        "knownFrequencies": [true, false, true],
+        "knownSignals": {"Traveler_Chert": true},
+        "dictConditions": {},
+        "persistentConditions": {"MET_SOLANUM": false},
+        "shipLogFactSaves": {
+            "TH_VILLAGE_X1": {"id": "TH_VILLAGE_X1", "revealOrder": 0, "read": true, "newlyRevealed": false},
+            "TH_VILLAGE_X2": {"id": "TH_VILLAGE_X2", "revealOrder": -1, "read": false, "newlyRevealed": false},
+            "OLD_FACT": {"id": "OLD_FACT", "revealOrder": 3, "read": false, "newlyRevealed": true}
+        },
+        "lastDeathType": 2
+    }"#;
+
+    #[test]
+    fn test_parse_save() {
+        let save = Save::from_slice(SAVE.as_bytes()).unwrap();
+        assert_eq!(save.loop_count, 12);
+        assert_eq!(save.known_frequencies, [true, false, true]);
+        assert!(save.known_signals["Traveler_Chert"]);
+        assert!(!save.persistent_conditions["MET_SOLANUM"]);
+        assert!(save.ship_log_fact_saves["OLD_FACT"].newly_revealed);
+        assert_eq!(
+            save.opened_facts(),
+            BTreeSet::from(["OLD_FACT", "TH_VILLAGE_X1"])
+        );
+    }
+
+    #[test]
+    fn test_compare_keys() {
+        let save = Save::from_slice(SAVE.as_bytes()).unwrap();
+        let keys = ["TH_VILLAGE_X1", "TH_VILLAGE_X2", "TH_VILLAGE_X3"]
+            .into_iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            save.compare_keys(&keys),
+            SaveKeysDiff {
+                only_in_data: vec!["TH_VILLAGE_X3".to_string()],
+                only_in_save: vec!["OLD_FACT".to_string()],
+            }
+        );
+    }
+}
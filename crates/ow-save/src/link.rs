@@ -0,0 +1,489 @@
+//! Share links, same as on site: `#v=1.1.16&ev=1&save=...`
+//!
+//! Encoding version 1: for each save key in sorted order, bit is set if fact
+//! is opened. Bits are packed from most significant one, bytes are encoded
+//! with standard base64. Keys are taken by game version of link, early links
+//! have no version and are made for [`V1_GAME_VERSION`]
+//!
+//! Encoding version 2 is base64url without padding of:
+//! - mode, 1 byte: `0` for bitmap, `1` for list of indices
+//! - fingerprint of key set, 4 bytes big-endian, see
+//!   [`crate::keys::fingerprint`]
+//! - bitmap: the same as in version 1, `ceil(keys / 8)` bytes
+//! - or list of indices: indices of opened facts in sorted keys, written as
+//!   gaps between them (first index, then `index - previous - 1`), each gap is
+//!   LEB128 varint
+//!
+//! Encoder writes the shorter one. Key set is found by fingerprint among known
+//! sets, so links stay valid when keys change in new game version. Site can't
+//! decode version 2 yet, so links are made with version 1 by default
+
+use std::{collections::BTreeSet, fmt::Display};
+
+use base64::{
+    Engine,
+    prelude::{BASE64_STANDARD, BASE64_URL_SAFE_NO_PAD},
+};
+
+use crate::{Save, keys::KeySet};
+
+/// Address of site, links are made for
+pub const SITE_URL: &str = "https://istudyatuni.github.io/ow-tracker/";
+
+/// Game version of version 1 links without version
+pub const V1_GAME_VERSION: &str = "1.1.16";
+
+/// Default encoding version of new links, the one site can decode
+pub const ENCODING_VERSION: u32 = 1;
+
+/// Latest supported encoding version
+pub const LATEST_ENCODING_VERSION: u32 = 2;
+
+const MODE_BITMAP: u8 = 0;
+const MODE_INDICES: u8 = 1;
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum LinkError {
+    #[error("link doesn't contain save")]
+    NoSave,
+
+    #[error("invalid encoding version {0:?}")]
+    InvalidVersion(String),
+
+    #[error("unsupported encoding version {0}")]
+    UnsupportedVersion(u32),
+
+    #[error("invalid base64: {0}")]
+    Base64(#[from] base64::DecodeError),
+
+    #[error("save contains {actual} facts, expected at least {expected}")]
+    TooShort { expected: usize, actual: usize },
+
+    #[error("no save keys for game version {0}")]
+    UnknownGameVersion(String),
+
+    #[error("no save keys with fingerprint {0:08x}")]
+    UnknownKeySet(u32),
+
+    #[error("invalid save: {0}")]
+    InvalidSave(&'static str),
+}
+
+/// Parts of share link
+#[derive(Debug, Clone, PartialEq)]
+pub struct Link {
+    /// Game version, missing in early links
+    pub game_version: Option<String>,
+    pub encoding_version: u32,
+    /// Encoded opened facts
+    pub save: String,
+}
+
+/// Facts, decoded from link
+#[derive(Debug, PartialEq)]
+pub struct Decoded {
+    /// Game version of key set, link is made with
+    pub game_version: String,
+    pub facts: BTreeSet<String>,
+}
+
+impl Link {
+    /// Link with facts, opened in save. `keys` are save keys of `game_version`
+    pub fn from_save(
+        save: &Save,
+        keys: &BTreeSet<String>,
+        game_version: &str,
+        encoding_version: u32,
+    ) -> Result<Self, LinkError> {
+        let opened = save.opened_facts();
+        let save = match encoding_version {
+            1 => encode_v1(keys, &opened),
+            2 => encode_v2(keys, &opened),
+            v => return Err(LinkError::UnsupportedVersion(v)),
+        };
+        Ok(Self {
+            game_version: Some(game_version.to_string()),
+            encoding_version,
+            save,
+        })
+    }
+
+    /// Parse full link or its fragment. Early links contain only `#save=`
+    pub fn parse(link: &str) -> Result<Self, LinkError> {
+        let fragment = link.split_once('#').map_or(link, |(_, f)| f);
+        let mut parsed = Self {
+            game_version: None,
+            encoding_version: 1,
+            save: String::new(),
+        };
+        let mut has_save = false;
+        for (key, value) in fragment.split('&').filter_map(|p| p.split_once('=')) {
+            match key {
+                "v" => parsed.game_version = Some(value.to_string()),
+                "ev" => {
+                    parsed.encoding_version = value
+                        .parse()
+                        .map_err(|_| LinkError::InvalidVersion(value.to_string()))?
+                }
+                // base64 padding is kept, because value is split at first `=`
+                "save" => {
+                    parsed.save = value.to_string();
+                    has_save = true;
+                }
+                _ => {}
+            }
+        }
+        if !has_save {
+            return Err(LinkError::NoSave);
+        }
+        Ok(parsed)
+    }
+
+    /// Link to site
+    pub fn to_url(&self, site: &str) -> String {
+        format!("{site}#{self}")
+    }
+
+    /// Decode opened facts with one of known key sets, see [`crate::keys`]
+    pub fn decode(&self, key_sets: &[KeySet]) -> Result<Decoded, LinkError> {
+        match self.encoding_version {
+            1 => {
+                let game_version = self.game_version.as_deref().unwrap_or(V1_GAME_VERSION);
+                let set = key_sets
+                    .iter()
+                    .find(|s| s.game_version == game_version)
+                    .ok_or_else(|| LinkError::UnknownGameVersion(game_version.to_string()))?;
+                Ok(Decoded {
+                    game_version: set.game_version.clone(),
+                    facts: decode_v1(&set.keys, &self.save)?,
+                })
+            }
+            2 => {
+                let (set, facts) = decode_v2(key_sets, &self.save)?;
+                Ok(Decoded {
+                    game_version: set.game_version.clone(),
+                    facts,
+                })
+            }
+            v => Err(LinkError::UnsupportedVersion(v)),
+        }
+    }
+}
+
+impl Display for Link {
+    /// Fragment of link, without `#`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(v) = &self.game_version {
+            write!(f, "v={v}&")?;
+        }
+        write!(f, "ev={}&save={}", self.encoding_version, self.save)
+    }
+}
+
+/// Encode opened facts
+pub fn encode_v1<'k>(
+    keys: impl IntoIterator<Item = &'k String>,
+    opened: &BTreeSet<&str>,
+) -> String {
+    let mut keys: Vec<_> = keys.into_iter().collect();
+    keys.sort();
+    let bools: Vec<_> = keys.iter().map(|k| opened.contains(k.as_str())).collect();
+    BASE64_STANDARD.encode(pack_bools(&bools))
+}
+
+/// Decode opened facts. There can be padding bits after last key
+pub fn decode_v1(keys: &BTreeSet<String>, encoded: &str) -> Result<BTreeSet<String>, LinkError> {
+    let bools = unpack_bools(&BASE64_STANDARD.decode(encoded)?);
+    if bools.len() < keys.len() {
+        return Err(LinkError::TooShort {
+            expected: keys.len(),
+            actual: bools.len(),
+        });
+    }
+    Ok(keys
+        .iter()
+        .zip(bools)
+        .filter(|(_, opened)| *opened)
+        .map(|(k, _)| k.clone())
+        .collect())
+}
+
+/// Encode opened facts, choosing the shorter of bitmap and list of indices
+pub fn encode_v2(keys: &BTreeSet<String>, opened: &BTreeSet<&str>) -> String {
+    let bools: Vec<_> = keys.iter().map(|k| opened.contains(k.as_str())).collect();
+    let bitmap = pack_bools(&bools);
+
+    let mut indices = vec![];
+    let mut next = 0;
+    for (i, _) in bools.iter().enumerate().filter(|(_, b)| **b) {
+        write_varint(&mut indices, i - next);
+        next = i + 1;
+    }
+
+    let (mode, body) = if indices.len() < bitmap.len() {
+        (MODE_INDICES, indices)
+    } else {
+        (MODE_BITMAP, bitmap)
+    };
+    let mut data = vec![mode];
+    data.extend(crate::keys::fingerprint(keys).to_be_bytes());
+    data.extend(body);
+    BASE64_URL_SAFE_NO_PAD.encode(data)
+}
+
+/// Decode opened facts. Returns key set, matching fingerprint in save
+pub fn decode_v2<'s>(
+    key_sets: &'s [KeySet],
+    encoded: &str,
+) -> Result<(&'s KeySet, BTreeSet<String>), LinkError> {
+    let data = BASE64_URL_SAFE_NO_PAD.decode(encoded)?;
+    let [mode, f0, f1, f2, f3, body @ ..] = data.as_slice() else {
+        return Err(LinkError::InvalidSave("missing header"));
+    };
+    let fingerprint = u32::from_be_bytes([*f0, *f1, *f2, *f3]);
+    let set = key_sets
+        .iter()
+        .find(|s| s.fingerprint() == fingerprint)
+        .ok_or(LinkError::UnknownKeySet(fingerprint))?;
+    let keys: Vec<_> = set.keys.iter().collect();
+
+    let mut facts = BTreeSet::new();
+    match *mode {
+        MODE_BITMAP => {
+            if body.len() != keys.len().div_ceil(8) {
+                return Err(LinkError::InvalidSave("wrong length of bitmap"));
+            }
+            for (key, opened) in keys.iter().zip(unpack_bools(body)) {
+                if opened {
+                    facts.insert(key.to_string());
+                }
+            }
+        }
+        MODE_INDICES => {
+            let mut body = body;
+            let mut next = 0;
+            while !body.is_empty() {
+                let index = next + read_varint(&mut body)?;
+                let key = keys
+                    .get(index)
+                    .ok_or(LinkError::InvalidSave("index out of range"))?;
+                facts.insert(key.to_string());
+                next = index + 1;
+            }
+        }
+        _ => return Err(LinkError::InvalidSave("unknown mode")),
+    }
+    Ok((set, facts))
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(data: &mut &[u8]) -> Result<usize, LinkError> {
+    let mut value = 0usize;
+    for shift in (0..usize::BITS).step_by(7) {
+        let [byte, rest @ ..] = *data else {
+            return Err(LinkError::InvalidSave("unfinished index"));
+        };
+        *data = rest;
+        value |= ((byte & 0x7f) as usize) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(LinkError::InvalidSave("too big index"))
+}
+
+/// Pack bools to bytes, first bool is the most significant bit
+pub fn pack_bools(bools: &[bool]) -> Vec<u8> {
+    bools
+        .chunks(8)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .enumerate()
+                .filter(|(_, b)| **b)
+                .fold(0, |byte, (bit, _)| byte | (1 << (7 - bit)))
+        })
+        .collect()
+}
+
+pub fn unpack_bools(bytes: &[u8]) -> Vec<bool> {
+    bytes
+        .iter()
+        .flat_map(|byte| (0..8).rev().map(move |bit| (byte >> bit) & 1 == 1))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FactSave, keys::key_sets};
+
+    /// Link from README, generated by site
+    const TUTORIAL_LINK: &str = "https://istudyatuni.github.io/ow-tracker/#save=AAAAAAAAAAAAAAAAAAAAAAAAAEAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAPxAAAAA=";
+    const TUTORIAL_FACTS: &[&str] = &[
+        "GD_GABBRO_ISLAND_R1",
+        "TH_VILLAGE_X1",
+        "TH_VILLAGE_X2",
+        "TH_VILLAGE_X3",
+        "TH_ZERO_G_CAVE_R1",
+        "TH_ZERO_G_CAVE_X1",
+        "TH_ZERO_G_CAVE_X2",
+        "TM_EYE_LOCATOR_R2",
+    ];
+
+    #[test]
+    fn test_pack_bools() {
+        let bools = [true, false, false, false, false, false, false, true, true];
+        assert_eq!(pack_bools(&bools), [0b1000_0001, 0b1000_0000]);
+        assert_eq!(&unpack_bools(&pack_bools(&bools))[..9], bools);
+    }
+
+    #[test]
+    fn test_site_link() {
+        let key_sets = key_sets();
+        let link = Link::parse(TUTORIAL_LINK).unwrap();
+        assert_eq!(link.game_version, None);
+        let decoded = link.decode(&key_sets).unwrap();
+        assert_eq!(decoded.game_version, "1.1.16");
+        assert_eq!(decoded.facts.iter().collect::<Vec<_>>(), TUTORIAL_FACTS);
+
+        let opened = decoded.facts.iter().map(String::as_str).collect();
+        assert_eq!(encode_v1(&key_sets[0].keys, &opened), link.save);
+
+        // current links of site
+        let link = Link::parse(&format!("#v=1.1.16&ev=1&save={}", link.save)).unwrap();
+        assert_eq!(link.decode(&key_sets).unwrap(), decoded);
+    }
+
+    #[test]
+    fn test_link_from_save() {
+        let key_sets = key_sets();
+        let keys = &key_sets[0].keys;
+        let mut save = Save::default();
+        for id in keys {
+            let reveal_order = match TUTORIAL_FACTS.binary_search(&id.as_str()) {
+                Ok(i) => i as i32,
+                Err(_) => -1,
+            };
+            save.ship_log_fact_saves.insert(
+                id.clone(),
+                FactSave {
+                    id: id.clone(),
+                    reveal_order,
+                    ..Default::default()
+                },
+            );
+        }
+        let link = Link::from_save(&save, keys, "1.1.16", ENCODING_VERSION).unwrap();
+        assert_eq!(
+            link.to_url(SITE_URL),
+            format!(
+                "https://istudyatuni.github.io/ow-tracker/#v=1.1.16&ev=1&save={}",
+                Link::parse(TUTORIAL_LINK).unwrap().save
+            )
+        );
+        let v2 = Link::from_save(&save, keys, "1.1.16", 2).unwrap();
+        // sparse save is written as list of indices
+        assert_eq!(
+            v2.to_url(SITE_URL),
+            "https://istudyatuni.github.io/ow-tracker/#v=1.1.16&ev=2&save=AQo_J_6ZAbgBAAAAAAAD"
+        );
+        for link in [link, v2] {
+            let decoded = Link::parse(&link.to_url(SITE_URL))
+                .unwrap()
+                .decode(&key_sets)
+                .unwrap();
+            assert_eq!(decoded.facts.iter().collect::<Vec<_>>(), TUTORIAL_FACTS);
+        }
+        assert_eq!(
+            Link::from_save(&save, keys, "1.1.16", 3),
+            Err(LinkError::UnsupportedVersion(3))
+        );
+    }
+
+    #[test]
+    fn test_v2() {
+        let old = KeySet {
+            game_version: "1".to_string(),
+            keys: (0..200).map(|i| format!("FACT_{i:03}")).collect(),
+        };
+        let mut new = old.clone();
+        new.game_version = "2".to_string();
+        new.keys.insert("FACT_000_NEW".to_string());
+        let key_sets = [old.clone(), new.clone()];
+
+        let all: BTreeSet<_> = old.keys.iter().map(String::as_str).collect();
+        let few = BTreeSet::from(["FACT_000", "FACT_150", "FACT_199"]);
+        for opened in [all, few, BTreeSet::new()] {
+            let encoded = encode_v2(&old.keys, &opened);
+            let (set, facts) = decode_v2(&key_sets, &encoded).unwrap();
+            // link is decoded with keys it was made with
+            assert_eq!(set.game_version, "1");
+            assert_eq!(
+                facts.iter().map(String::as_str).collect::<BTreeSet<_>>(),
+                opened
+            );
+        }
+
+        let data = BASE64_URL_SAFE_NO_PAD
+            .decode(encode_v2(&old.keys, &BTreeSet::from(["FACT_150"])))
+            .unwrap();
+        assert_eq!(data[0], MODE_INDICES);
+        assert_eq!(data[5..], [150, 1]);
+        let data = BASE64_URL_SAFE_NO_PAD
+            .decode(encode_v2(
+                &old.keys,
+                &old.keys.iter().map(String::as_str).collect(),
+            ))
+            .unwrap();
+        assert_eq!(data[0], MODE_BITMAP);
+        assert_eq!(data.len(), 5 + 25);
+
+        let encoded = encode_v2(&new.keys, &BTreeSet::new());
+        assert_eq!(
+            decode_v2(&key_sets[..1], &encoded),
+            Err(LinkError::UnknownKeySet(new.fingerprint()))
+        );
+        assert_eq!(
+            decode_v2(&key_sets, "AA"),
+            Err(LinkError::InvalidSave("missing header"))
+        );
+    }
+
+    #[test]
+    fn test_parse_link() {
+        let link = Link {
+            game_version: Some("1.1.16".to_string()),
+            encoding_version: 1,
+            save: "AAE=".to_string(),
+        };
+        let url = link.to_url(SITE_URL);
+        assert_eq!(
+            url,
+            "https://istudyatuni.github.io/ow-tracker/#v=1.1.16&ev=1&save=AAE="
+        );
+        assert_eq!(Link::parse(&url), Ok(link.clone()));
+        assert_eq!(Link::parse("v=1.1.16&ev=1&save=AAE="), Ok(link));
+        assert_eq!(Link::parse("#v=1.1.16"), Err(LinkError::NoSave));
+        assert_eq!(
+            Link::parse("#ev=x&save=AAE="),
+            Err(LinkError::InvalidVersion("x".to_string()))
+        );
+
+        let keys = BTreeSet::from(["A".to_string(), "B".to_string()]);
+        assert_eq!(
+            decode_v1(&keys, ""),
+            Err(LinkError::TooShort {
+                expected: 2,
+                actual: 0
+            })
+        );
+    }
+}
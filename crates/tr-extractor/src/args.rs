@@ -1,23 +1,154 @@
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{Args, Parser, Subcommand};
+use ow_save::link::{ENCODING_VERSION, LATEST_ENCODING_VERSION, SITE_URL};
+use tr_extractor::{
+    formats::TranslationFormat,
+    pack::{Compression, PackOptions},
+    richtext::RichTextMode,
+};
 
 /// Translation extractor for Outer Wilds
 #[derive(Debug, Parser)]
 pub struct Cli {
-    /// Path to game's data directory
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Path to game's directory or its data directory. Searched in Steam
+    /// libraries if not set
     #[arg(long)]
     pub data_dir: Option<PathBuf>,
 
+    /// Game version to use extraction profile for. Detected from game files
+    /// if not set
+    #[arg(long)]
+    pub game_version: Option<String>,
+
     /// Path to output directory
     #[arg(long = "output-dir", default_value = "output")]
     pub out_dir: PathBuf,
 
+    /// Format of translation files
+    #[arg(long, value_enum, default_value_t)]
+    pub format: TranslationFormat,
+
+    /// How to convert Unity markup in translations
+    #[arg(long, value_enum, default_value_t)]
+    pub rich_text: RichTextMode,
+
+    /// Keys of UI texts to write to `ui`. All texts from game's
+    /// UI table are written if not set
+    #[arg(long, value_delimiter = ',')]
+    pub ui_keys: Option<Vec<String>>,
+
+    #[command(flatten)]
+    pub pack: PackArgs,
+
     /// Write files
     #[arg(long)]
     pub write: bool,
 
+    /// Compare generated files with files in output directory without writing,
+    /// exit with error if they differ
+    #[arg(long, conflicts_with = "write")]
+    pub check: bool,
+
+    /// Extract card sprites to `sprites` in output directory
+    #[arg(long)]
+    pub sprites: bool,
+
+    /// Path to game save, to compare extracted save keys with keys in it
+    #[arg(long)]
+    pub compare_save: Option<PathBuf>,
+
     /// Verbose logging
-    #[arg(short, action = clap::ArgAction::Count)]
+    #[arg(short, global = true, action = clap::ArgAction::Count)]
     pub verbosity: u8,
 }
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Minify JSON files in directory and write compressed siblings of all
+    /// files, without extracting game data
+    Pack {
+        /// Directory with files, e.g. `frontend/public`
+        dir: PathBuf,
+
+        #[command(flatten)]
+        pack: PackArgs,
+    },
+
+    /// Make share link from game save
+    SaveLink {
+        /// Path to game save, `data.owsave`
+        save: PathBuf,
+
+        #[command(flatten)]
+        keys: KeysArgs,
+
+        /// Address of site
+        #[arg(long, default_value = SITE_URL)]
+        site: String,
+
+        /// Encoding version of link. Version 2 is shorter and survives new
+        /// save keys, but site can't open it yet
+        #[arg(
+            long,
+            default_value_t = ENCODING_VERSION,
+            value_parser = clap::value_parser!(u32).range(1..=LATEST_ENCODING_VERSION as i64)
+        )]
+        encoding: u32,
+    },
+
+    /// Print facts, opened in share link
+    LinkFacts {
+        /// Share link or its part after `#`
+        link: String,
+
+        #[command(flatten)]
+        keys: KeysArgs,
+    },
+
+    /// Print exploration progress of game save
+    Progress {
+        /// Path to game save, `data.owsave`
+        #[arg(long)]
+        save: PathBuf,
+
+        /// Path to generated entries
+        #[arg(long, default_value = "output/entries.json")]
+        entries: PathBuf,
+
+        /// Print progress as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct KeysArgs {
+    /// Directory with generated `save_keys.json` and `meta.json`, e.g.
+    /// `frontend/public`
+    #[arg(long, default_value = "output")]
+    pub keys_dir: PathBuf,
+}
+
+#[derive(Debug, Args)]
+pub struct PackArgs {
+    /// Write JSON without whitespace
+    #[arg(long)]
+    pub compact: bool,
+
+    /// Also write compressed copies of files, e.g. `entries.json.gz`
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub compress: Vec<Compression>,
+}
+
+impl PackArgs {
+    pub fn options(&self) -> PackOptions {
+        PackOptions {
+            compact: self.compact,
+            compress: self.compress.clone(),
+        }
+    }
+}
@@ -0,0 +1,483 @@
+//! Loading of astro objects and translation tables from asset files
+
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fs::File,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use memmap2::Mmap;
+use tracing::{debug, info, warn};
+
+use crate::{
+    MORE_TO_EXPLORE_EXTRACT_KEY,
+    info::Lang,
+    models::{
+        entries::{AstroObject, JsonEntry, XmlEntry},
+        translations::{Translation, Translations},
+    },
+    scan::Scanner,
+    unity::{SerializedFile, TextAsset, UnityError},
+};
+
+/// Name of `TextAsset`s with translations
+const TR_TEXT_ASSET_NAME: &str = "TranslationTable_XML";
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Objects extracted from all asset files
+pub struct Assets {
+    pub astro_objects: Vec<AstroObject<JsonEntry>>,
+
+    /// Map of astro object id to name of file it was found in
+    pub sources: HashMap<String, String>,
+
+    pub tr_objects: Vec<TrObject>,
+}
+
+/// Translation table with name of file it was found in
+#[derive(Debug)]
+pub struct TrObject {
+    pub file: String,
+    /// Ship log table and "There's more to explore" from UI table
+    pub entries: Vec<Translation>,
+    /// Full UI table
+    pub ui: Vec<Translation>,
+}
+
+/// List all asset files in data dir: `*.assets` and `level*`
+pub fn asset_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    for entry in std::fs::read_dir(dir).context("reading data dir")? {
+        let entry = entry?;
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        let name = entry.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        if name.ends_with(".assets") || (name.starts_with("level") && !name.contains('.')) {
+            files.push(entry.path());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Memory-mapped asset file
+pub struct MappedFile {
+    pub name: String,
+    pub mmap: Mmap,
+}
+
+/// Map all asset files in data dir
+pub fn map_asset_files(dir: &Path) -> Result<Vec<MappedFile>> {
+    let mut files = vec![];
+    for path in asset_files(dir)? {
+        let name = path
+            .file_name()
+            .expect("asset file should have name")
+            .to_string_lossy()
+            .to_string();
+        let file = File::open(&path).with_context(|| format!("opening {name}"))?;
+        files.push(MappedFile {
+            name,
+            mmap: unsafe { Mmap::map(&file)? },
+        });
+    }
+    Ok(files)
+}
+
+/// Parse mapped files as serialized files, skipping files which can't be
+/// parsed
+pub fn parse_asset_files(files: &[MappedFile]) -> Vec<(&str, SerializedFile<'_>)> {
+    files
+        .iter()
+        .filter_map(|f| match SerializedFile::parse(&f.mmap) {
+            Ok(file) => Some((f.name.as_str(), file)),
+            Err(e) => {
+                debug!("skipping {}: {e}", f.name);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Extract astro objects and translations from all asset files in data dir
+pub fn load_assets(dir: &Path, scanner: &Scanner) -> Result<Assets> {
+    let mut assets = Assets {
+        astro_objects: Vec::with_capacity(100),
+        sources: HashMap::new(),
+        tr_objects: Vec::with_capacity(Lang::ALL.len()),
+    };
+    // hashes of tables, same table can be found in different files
+    let mut seen_tables = HashSet::new();
+
+    for path in asset_files(dir)? {
+        let file_name = path
+            .file_name()
+            .expect("asset file should have name")
+            .to_string_lossy()
+            .to_string();
+        let file = File::open(&path).with_context(|| format!("opening {file_name}"))?;
+        let mmap = unsafe { Mmap::map(&file)? };
+        let (astro_objects, tables) = extract_objects(&mmap, &file_name, scanner)?;
+
+        for object in astro_objects {
+            let astro_object = parse_astro_object(object)
+                .with_context(|| format!("parsing astro object from {file_name}"))?;
+            if let Some(source) = assets.sources.get(&astro_object.id) {
+                debug!(
+                    "skipping duplicate {} from {file_name}, already found in {source}",
+                    astro_object.id
+                );
+                continue;
+            }
+            info!("extracted {} from {file_name}", astro_object.id);
+            assets
+                .sources
+                .insert(astro_object.id.clone(), file_name.clone());
+            assets.astro_objects.push(astro_object.into());
+        }
+
+        for (shiplog, ui) in tables {
+            let mut hasher = DefaultHasher::new();
+            (shiplog, ui).hash(&mut hasher);
+            if !seen_tables.insert(hasher.finish()) {
+                debug!("skipping duplicate translation table from {file_name}");
+                continue;
+            }
+            let mut objects = parse_tr_object(shiplog)
+                .with_context(|| format!("parsing tr object from {file_name}"))?
+                .entries;
+            let ui = parse_tr_object(ui)
+                .with_context(|| format!("parsing tr object from {file_name}"))?
+                .entries;
+            objects.extend(
+                ui.iter()
+                    .filter(|t| t.key == MORE_TO_EXPLORE_EXTRACT_KEY)
+                    .cloned(),
+            );
+            info!("extracted translation table from {file_name}");
+            assets.tr_objects.push(TrObject {
+                file: file_name.clone(),
+                entries: objects,
+                ui,
+            });
+        }
+    }
+
+    if assets.astro_objects.is_empty() {
+        bail!("no astro objects found in {}", dir.display());
+    }
+    if assets.tr_objects.is_empty() {
+        bail!("no translation tables found in {}", dir.display());
+    }
+
+    Ok(assets)
+}
+
+/// Find ship log XMLs and pairs of shiplog and ui translation tables in asset
+/// file. They are read from `TextAsset`s, if file can't be parsed, search for
+/// markers instead. When no `TextAsset` starts with ship log root element,
+/// ship log XMLs are searched by markers in all of them
+#[allow(clippy::type_complexity)]
+fn extract_objects<'d>(
+    data: &'d [u8],
+    file_name: &str,
+    scanner: &Scanner,
+) -> Result<(Vec<&'d str>, Vec<(&'d str, &'d str)>)> {
+    let assets = match find_text_assets(data) {
+        Ok(assets) => assets,
+        Err(e) => {
+            warn!("failed to read {file_name}: {e}, falling back to searching markers");
+            return scan_objects(data, scanner);
+        }
+    };
+
+    let mut astro_objects = vec![];
+    let mut tables = vec![];
+    let mut other = vec![];
+    for a in assets {
+        let script = a.script.strip_prefix(UTF8_BOM).unwrap_or(a.script);
+        if a.name == TR_TEXT_ASSET_NAME {
+            match scan_objects(script, scanner)?.1.as_slice() {
+                [table] => tables.push(*table),
+                _ => bail!("text asset {} should contain one pair of tables", a.name),
+            }
+        } else if xml_root(script).starts_with(b"<AstroObjectEntry>") {
+            astro_objects.push(std::str::from_utf8(script)?);
+        } else {
+            other.push(script);
+        }
+    }
+    if astro_objects.is_empty() {
+        for script in other {
+            for range in scanner.scan(script).astro_objects {
+                astro_objects.push(std::str::from_utf8(&script[range])?);
+            }
+        }
+    }
+    Ok((astro_objects, tables))
+}
+
+/// Skip whitespace and XML declaration before root element
+fn xml_root(data: &[u8]) -> &[u8] {
+    let data = data.trim_ascii_start();
+    match data.strip_prefix(b"<?xml") {
+        Some(rest) => match rest.windows(2).position(|w| w == b"?>") {
+            Some(end) => rest[end + 2..].trim_ascii_start(),
+            None => data,
+        },
+        None => data,
+    }
+}
+
+/// Find all `TextAsset`s in serialized file
+fn find_text_assets(data: &[u8]) -> Result<Vec<TextAsset<'_>>, UnityError> {
+    let file = SerializedFile::parse(data)?;
+    debug!(
+        "serialized file version {}, unity {}, {} objects",
+        file.version,
+        file.unity_version,
+        file.objects.len()
+    );
+
+    let mut assets = vec![];
+    for asset in file.text_assets() {
+        let asset = asset?;
+        debug!("found text asset {}", asset.name);
+        assets.push(asset);
+    }
+    Ok(assets)
+}
+
+/// Search for ship log XMLs and pairs of shiplog and ui translation tables by
+/// markers
+#[allow(clippy::type_complexity)]
+fn scan_objects<'d>(
+    data: &'d [u8],
+    scanner: &Scanner,
+) -> Result<(Vec<&'d str>, Vec<(&'d str, &'d str)>)> {
+    let index = scanner.scan(data);
+    let astro_objects = index
+        .astro_objects
+        .into_iter()
+        .map(|r| Ok(std::str::from_utf8(&data[r])?))
+        .collect::<Result<_>>()?;
+    if index.shiplog_tables.len() != index.ui_tables.len() {
+        bail!(
+            "found {} shiplog tables, but {} ui tables",
+            index.shiplog_tables.len(),
+            index.ui_tables.len()
+        );
+    }
+    let tables = index
+        .shiplog_tables
+        .into_iter()
+        .zip(index.ui_tables)
+        .map(|(shiplog, ui)| {
+            Ok((
+                std::str::from_utf8(&data[shiplog])?,
+                std::str::from_utf8(&data[ui])?,
+            ))
+        })
+        .collect::<Result<_>>()?;
+    Ok((astro_objects, tables))
+}
+
+pub fn parse_tr_object(data: &str) -> Result<Translations> {
+    Ok(serde_xml_rs::from_str(data)?)
+}
+
+/// Parse ship log XML. Elements, which are not modeled, are reported
+pub fn parse_astro_object(data: &str) -> Result<AstroObject<XmlEntry>> {
+    let (object, ignored) = parse_astro_object_ignored(data)?;
+    for path in ignored {
+        warn!("unknown element {path} in astro object {}", object.id);
+    }
+    Ok(object)
+}
+
+/// Parse ship log XML and collect paths of elements, which are not modeled
+fn parse_astro_object_ignored(data: &str) -> Result<(AstroObject<XmlEntry>, BTreeSet<String>)> {
+    let mut ignored = BTreeSet::new();
+    let mut de = serde_xml_rs::Deserializer::new_from_reader(data.as_bytes());
+    let object = serde_ignored::deserialize(&mut de, |path| {
+        ignored.insert(path.to_string());
+    })?;
+    Ok((object, ignored))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::entries::{ExploreFact, RumorFact},
+        unity::{
+            CLASS_TEXT_ASSET,
+            tests::{build_file, text_asset},
+        },
+    };
+
+    #[test]
+    fn test_load_assets() {
+        let dir = std::env::temp_dir().join(format!("tr-extractor-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let astro = |id: &str| format!("<AstroObjectEntry><ID>{id}</ID></AstroObjectEntry>");
+        let table = "<TranslationTable_XML><table_shipLog>\
+            <TranslationTableEntry><key>a</key><value>b</value></TranslationTableEntry>\
+            </table_shipLog><table_ui>\
+            <TranslationTableEntryUI><key>973</key><value>c</value></TranslationTableEntryUI>\
+            </table_ui></TranslationTable_XML>";
+        let files = [
+            (
+                "sharedassets1.assets",
+                build_file(&[(CLASS_TEXT_ASSET, text_asset("TimberHearth", &astro("TH")))]),
+            ),
+            (
+                "sharedassets2.assets",
+                build_file(&[
+                    (CLASS_TEXT_ASSET, text_asset("TimberHearth", &astro("TH"))),
+                    (CLASS_TEXT_ASSET, text_asset("Ship", &astro("SHIP"))),
+                ]),
+            ),
+            // not serialized file, should be searched for markers
+            ("level3", format!("\0\0{table}\0").into_bytes()),
+            (
+                "resources.assets",
+                build_file(&[(CLASS_TEXT_ASSET, text_asset(TR_TEXT_ASSET_NAME, table))]),
+            ),
+            ("level3.resS", astro("IGNORED").into_bytes()),
+            // markers are searched only in files, which can't be parsed
+            (
+                "sharedassets4.assets",
+                build_file(&[(1, astro("NOT_TEXT_ASSET").into_bytes())]),
+            ),
+            // BOM and XML declaration
+            (
+                "sharedassets5.assets",
+                build_file(&[(
+                    CLASS_TEXT_ASSET,
+                    text_asset(
+                        "Comet",
+                        &format!("\u{feff}<?xml version=\"1.0\"?>\n{}", astro("COMET")),
+                    ),
+                )]),
+            ),
+            // doesn't start with root element, searched by markers
+            (
+                "sharedassets6.assets",
+                build_file(&[(
+                    CLASS_TEXT_ASSET,
+                    text_asset("Moon", &format!("<!-- moon -->{}", astro("MOON"))),
+                )]),
+            ),
+        ];
+        for (name, data) in files {
+            std::fs::write(dir.join(name), data).unwrap();
+        }
+
+        let assets = load_assets(&dir, &Scanner::new());
+        std::fs::remove_dir_all(&dir).unwrap();
+        let assets = assets.unwrap();
+
+        let ids: Vec<_> = assets.astro_objects.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids, ["TH", "SHIP", "COMET", "MOON"]);
+        assert_eq!(assets.sources["TH"], "sharedassets1.assets");
+        assert_eq!(assets.sources["SHIP"], "sharedassets2.assets");
+        assert_eq!(assets.tr_objects.len(), 1);
+        assert_eq!(assets.tr_objects[0].file, "level3");
+        assert_eq!(assets.tr_objects[0].entries.len(), 2);
+        assert_eq!(assets.tr_objects[0].ui.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_astro_object() {
+        let data = r#"
+<AstroObjectEntry>
+<ID>TIMBER_HEARTH</ID>
+<Entry>
+    <ID>TH_VILLAGE</ID>
+    <Name>Village</Name>
+    <Curiosity>1</Curiosity>
+    <ExploreFact>
+        <ID>TH_VILLAGE_X1</ID>
+        <Text>2</Text>
+    </ExploreFact>
+    <Entry>
+        <ID>TH_ZERO_G_CAVE</ID>
+        <Name>Zero-G Cave</Name>
+        <AltPhotoCondition>TH_ZERO_G_CAVE_X1</AltPhotoCondition>
+        <ParentIgnoreNotRevealed/>
+        <RumorFact>
+            <ID>TH_ZERO_G_CAVE_R1</ID>
+            <SourceID>5</SourceID>
+            <RumorName>6</RumorName>
+            <RumorNamePriority>0</RumorNamePriority>
+            <Text>3</Text>
+        </RumorFact>
+        <ExploreFact>
+            <ID>TH_ZERO_G_CAVE_X1</ID>
+            <ClueType>7</ClueType>
+            <IgnoreMoreToExplore/>
+            <NewField>8</NewField>
+            <Text>4</Text>
+        </ExploreFact>
+    </Entry>
+</Entry>
+</AstroObjectEntry>
+        "#;
+
+        let (parsed, ignored) = parse_astro_object_ignored(data).unwrap();
+        let expected = AstroObject::builder()
+            .id("TIMBER_HEARTH".to_string())
+            .entries(vec![
+                XmlEntry::builder()
+                    .id("TH_VILLAGE".to_string())
+                    .name("Village".to_string())
+                    .curiosity("1".to_string())
+                    .explore_facts(vec![
+                        ExploreFact::builder()
+                            .id("TH_VILLAGE_X1".to_string())
+                            .text("2".to_string())
+                            .build(),
+                    ])
+                    .entries(vec![
+                        XmlEntry::builder()
+                            .id("TH_ZERO_G_CAVE".to_string())
+                            .name("Zero-G Cave".to_string())
+                            .alt_photo_condition("TH_ZERO_G_CAVE_X1".to_string())
+                            .parent_ignore_not_revealed(true)
+                            .rumor_facts(vec![
+                                RumorFact::builder()
+                                    .id("TH_ZERO_G_CAVE_R1".to_string())
+                                    .source_id("5".to_string())
+                                    .name("6".to_string())
+                                    .name_priority(0)
+                                    .text("3".to_string())
+                                    .build(),
+                            ])
+                            .explore_facts(vec![
+                                ExploreFact::builder()
+                                    .id("TH_ZERO_G_CAVE_X1".to_string())
+                                    .clue_type("7".to_string())
+                                    .ignore_more_to_explore(true)
+                                    .text("4".to_string())
+                                    .build(),
+                            ])
+                            .build(),
+                    ])
+                    .build(),
+            ])
+            .build();
+        similar_asserts::assert_eq!(expected, parsed);
+        assert_eq!(
+            ignored.into_iter().collect::<Vec<_>>(),
+            ["Entry.0.Entry.0.ExploreFact.0.NewField"]
+        );
+    }
+}
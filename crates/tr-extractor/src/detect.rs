@@ -0,0 +1,266 @@
+//! Detect language of translation table by its contents
+//!
+//! Tables with non-latin script are detected by share of characters from
+//! script. Keys of translation tables are english texts, so english table is
+//! detected by values, equal to the end of keys. Other latin languages are
+//! detected by frequent words
+
+use std::collections::HashMap;
+
+use crate::{info::Lang, models::translations::Translation};
+
+/// Minimal share of letters from language's script to consider it
+const SCRIPT_THRESHOLD: f64 = 0.5;
+
+/// Minimal share of values, equal to keys, to consider table english
+const ENGLISH_THRESHOLD: f64 = 0.5;
+
+/// Best score should be at least this times bigger than the next one
+const AMBIGUITY_RATIO: f64 = 1.5;
+
+/// Frequent words, which are rare in other supported languages
+const STOPWORDS: &[(Lang, &[&str])] = &[
+    (
+        Lang::SpanishLa,
+        &[
+            "el", "los", "las", "del", "pero", "muy", "hay", "y", "lo", "su", "sus", "parece",
+        ],
+    ),
+    (
+        Lang::PortugueseBr,
+        &[
+            "não", "uma", "os", "do", "da", "dos", "das", "com", "em", "mais", "ao", "são", "você",
+        ],
+    ),
+    (
+        Lang::Italian,
+        &[
+            "il", "della", "di", "che", "non", "è", "per", "gli", "sono", "anche", "questo",
+            "alla", "dei", "nel",
+        ],
+    ),
+    (
+        Lang::French,
+        &[
+            "le", "les", "des", "est", "une", "pas", "et", "du", "dans", "ce", "qui", "sur", "au",
+            "avec",
+        ],
+    ),
+    (
+        Lang::German,
+        &[
+            "der", "die", "und", "ist", "nicht", "das", "ein", "eine", "zu", "den", "mit", "sich",
+            "auf", "auch",
+        ],
+    ),
+    (
+        Lang::Polish,
+        &[
+            "nie", "się", "jest", "na", "to", "w", "z", "że", "jak", "czy", "ale", "przez",
+        ],
+    ),
+    (
+        Lang::Turkish,
+        &[
+            "bir", "ve", "bu", "için", "ile", "olarak", "ama", "gibi", "daha", "çok",
+        ],
+    ),
+];
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum DetectError {
+    #[error("translation table is empty")]
+    Empty,
+
+    #[error("can't detect language of translation table")]
+    Unknown,
+
+    #[error("ambiguous language of translation table, candidates: {}", fmt_langs(.0))]
+    Ambiguous(Vec<Lang>),
+}
+
+/// Detect language of translation table
+pub fn detect_lang(table: &[Translation]) -> Result<Lang, DetectError> {
+    if table.is_empty() {
+        return Err(DetectError::Empty);
+    }
+
+    if let Some(lang) = detect_by_script(table)? {
+        return Ok(lang);
+    }
+
+    let english = table
+        .iter()
+        .filter(|t| !t.value.is_empty() && t.key.ends_with(&t.value))
+        .count() as f64
+        / table.len() as f64;
+    if english >= ENGLISH_THRESHOLD {
+        return Ok(Lang::English);
+    }
+
+    detect_by_words(table)
+}
+
+fn detect_by_script(table: &[Translation]) -> Result<Option<Lang>, DetectError> {
+    let (mut letters, mut hangul, mut kana, mut han, mut cyrillic) = (0, 0, 0, 0, 0);
+    for ch in table.iter().flat_map(|t| t.value.chars()) {
+        if !ch.is_alphabetic() {
+            continue;
+        }
+        letters += 1;
+        match ch {
+            '\u{AC00}'..='\u{D7AF}' | '\u{1100}'..='\u{11FF}' | '\u{3130}'..='\u{318F}' => {
+                hangul += 1
+            }
+            '\u{3040}'..='\u{30FF}' => kana += 1,
+            '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}' => han += 1,
+            '\u{0400}'..='\u{04FF}' => cyrillic += 1,
+            _ => {}
+        }
+    }
+    if letters == 0 {
+        return Err(DetectError::Unknown);
+    }
+
+    let share = |count: u32| count as f64 / letters as f64;
+    let lang = if share(hangul) >= SCRIPT_THRESHOLD {
+        Lang::Korean
+    } else if share(cyrillic) >= SCRIPT_THRESHOLD {
+        Lang::Russian
+    } else if share(kana + han) >= SCRIPT_THRESHOLD {
+        // chinese doesn't use kana at all, while it's frequent in japanese
+        if share(kana) > 0.05 {
+            Lang::Japanese
+        } else {
+            Lang::ChineseSimple
+        }
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(lang))
+}
+
+fn detect_by_words(table: &[Translation]) -> Result<Lang, DetectError> {
+    let mut scores: HashMap<Lang, u32> = HashMap::new();
+    for word in table
+        .iter()
+        .flat_map(|t| t.value.split(|ch: char| !ch.is_alphabetic()))
+        .filter(|w| !w.is_empty())
+    {
+        let word = word.to_lowercase();
+        for (lang, words) in STOPWORDS {
+            if words.contains(&word.as_str()) {
+                *scores.entry(*lang).or_default() += 1;
+            }
+        }
+    }
+
+    let mut scores: Vec<_> = scores.into_iter().collect();
+    scores.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.file_name().cmp(b.0.file_name())));
+    match scores.as_slice() {
+        [] => Err(DetectError::Unknown),
+        [(lang, _)] => Ok(*lang),
+        [(lang, best), (_, next), ..] if *best as f64 >= *next as f64 * AMBIGUITY_RATIO => {
+            Ok(*lang)
+        }
+        [(_, best), ..] => Err(DetectError::Ambiguous(
+            scores
+                .iter()
+                .filter(|(_, s)| *s as f64 * AMBIGUITY_RATIO > *best as f64)
+                .map(|(l, _)| *l)
+                .collect(),
+        )),
+    }
+}
+
+fn fmt_langs(langs: &[Lang]) -> String {
+    langs
+        .iter()
+        .map(|l| l.file_name())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    const ENGLISH: &str = include_str!("../../../frontend/public/translations/english.json");
+    const TRANSLATIONS: &[(Lang, &str)] = &[
+        (
+            Lang::English,
+            include_str!("../../../frontend/public/translations/english.json"),
+        ),
+        (
+            Lang::SpanishLa,
+            include_str!("../../../frontend/public/translations/spanish_la.json"),
+        ),
+        (
+            Lang::German,
+            include_str!("../../../frontend/public/translations/german.json"),
+        ),
+        (
+            Lang::French,
+            include_str!("../../../frontend/public/translations/french.json"),
+        ),
+        (
+            Lang::Italian,
+            include_str!("../../../frontend/public/translations/italian.json"),
+        ),
+        (
+            Lang::Polish,
+            include_str!("../../../frontend/public/translations/polish.json"),
+        ),
+        (
+            Lang::PortugueseBr,
+            include_str!("../../../frontend/public/translations/portuguese_br.json"),
+        ),
+        (
+            Lang::Japanese,
+            include_str!("../../../frontend/public/translations/japanese.json"),
+        ),
+        (
+            Lang::Russian,
+            include_str!("../../../frontend/public/translations/russian.json"),
+        ),
+        (
+            Lang::ChineseSimple,
+            include_str!("../../../frontend/public/translations/chinese_simple.json"),
+        ),
+        (
+            Lang::Korean,
+            include_str!("../../../frontend/public/translations/korean.json"),
+        ),
+        (
+            Lang::Turkish,
+            include_str!("../../../frontend/public/translations/turkish.json"),
+        ),
+    ];
+
+    /// Build table with english texts as keys, like in game files
+    fn table(data: &str) -> Vec<Translation> {
+        let english: BTreeMap<String, String> = serde_json::from_str(ENGLISH).unwrap();
+        let translated: BTreeMap<String, String> = serde_json::from_str(data).unwrap();
+        translated
+            .into_iter()
+            .map(|(id, value)| Translation {
+                key: english[&id].clone(),
+                value,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_detect_lang() {
+        for (lang, data) in TRANSLATIONS {
+            assert_eq!(detect_lang(&table(data)), Ok(*lang), "{}", lang.file_name());
+        }
+    }
+
+    #[test]
+    fn test_detect_lang_empty() {
+        assert_eq!(detect_lang(&[]), Err(DetectError::Empty));
+    }
+}
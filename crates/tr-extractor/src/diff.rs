@@ -0,0 +1,205 @@
+//! Key-level comparison of JSON values
+//!
+//! Values are flattened to map of paths to leaf values. Arrays of objects with
+//! `"id"` field are keyed by id instead of index, so inserting an entry
+//! doesn't shift paths of all others. Order of ids is compared separately
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+#[derive(Debug, PartialEq)]
+pub enum Change {
+    Added {
+        key: String,
+        value: Value,
+    },
+    Removed {
+        key: String,
+        value: Value,
+    },
+    Changed {
+        key: String,
+        old: Value,
+        new: Value,
+    },
+    /// Ids, present in both arrays, are in different order
+    Reordered {
+        key: String,
+        old: Vec<String>,
+        new: Vec<String>,
+    },
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::Added { key, value } => write!(f, "+ {key}: {value}"),
+            Change::Removed { key, value } => write!(f, "- {key}: {value}"),
+            Change::Changed { key, old, new } => write!(f, "~ {key}: {old} -> {new}"),
+            Change::Reordered { key, old, new } => write!(
+                f,
+                "~ {key}: order [{}] -> [{}]",
+                old.join(", "),
+                new.join(", ")
+            ),
+        }
+    }
+}
+
+/// Compare two values and return list of changes from `old` to `new`, sorted
+/// by key
+pub fn diff(old: &Value, new: &Value) -> Vec<Change> {
+    let (old, old_orders) = flatten(old);
+    let (mut new, mut new_orders) = flatten(new);
+
+    let mut changes = vec![];
+    for (key, old) in old_orders {
+        let Some(new) = new_orders.remove(&key) else {
+            continue;
+        };
+        // added and removed ids are reported as values
+        let old: Vec<_> = old.into_iter().filter(|id| new.contains(id)).collect();
+        let new: Vec<_> = new.into_iter().filter(|id| old.contains(id)).collect();
+        if old != new {
+            changes.push(Change::Reordered { key, old, new });
+        }
+    }
+    for (key, old) in old {
+        match new.remove(&key) {
+            Some(new) if new == old => {}
+            Some(new) => changes.push(Change::Changed { key, old, new }),
+            None => changes.push(Change::Removed { key, value: old }),
+        }
+    }
+    changes.extend(
+        new.into_iter()
+            .map(|(key, value)| Change::Added { key, value }),
+    );
+    changes.sort_by(|a, b| a.key().cmp(b.key()));
+    changes
+}
+
+impl Change {
+    pub fn key(&self) -> &str {
+        match self {
+            Change::Added { key, .. }
+            | Change::Removed { key, .. }
+            | Change::Changed { key, .. }
+            | Change::Reordered { key, .. } => key,
+        }
+    }
+}
+
+/// Map of paths to leaf values, and map of paths to ids of arrays keyed by id
+type Flattened = (BTreeMap<String, Value>, BTreeMap<String, Vec<String>>);
+
+fn flatten(value: &Value) -> Flattened {
+    let mut out = (BTreeMap::new(), BTreeMap::new());
+    flatten_into(value, String::new(), &mut out);
+    out
+}
+
+fn flatten_into(value: &Value, prefix: String, out: &mut Flattened) {
+    let join = |key: &str| {
+        if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{prefix}.{key}")
+        }
+    };
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                flatten_into(value, join(key), out);
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            let ids: Option<Vec<&str>> = items
+                .iter()
+                .map(|v| v.get("id").and_then(Value::as_str))
+                .collect();
+            if let Some(ids) = &ids {
+                out.1.insert(
+                    prefix.clone(),
+                    ids.iter().map(|id| id.to_string()).collect(),
+                );
+            }
+            for (i, item) in items.iter().enumerate() {
+                let key = match &ids {
+                    Some(ids) => ids[i].to_string(),
+                    None => format!("[{i}]"),
+                };
+                flatten_into(item, join(&key), out);
+            }
+        }
+        _ => {
+            out.0.insert(prefix, value.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let old = json!([
+            {"id": "A", "facts": {"explore": [{"id": "A_X1"}, {"id": "A_X2"}]}},
+            {"id": "B", "curiosity": "X", "list": [1, 2]},
+        ]);
+        let new = json!([
+            {"id": "C"},
+            {"id": "A", "facts": {"explore": [{"id": "A_X1"}]}},
+            {"id": "B", "curiosity": "Y", "list": [1, 2]},
+        ]);
+        assert_eq!(
+            diff(&old, &new),
+            vec![
+                Change::Removed {
+                    key: "A.facts.explore.A_X2.id".to_string(),
+                    value: json!("A_X2")
+                },
+                Change::Changed {
+                    key: "B.curiosity".to_string(),
+                    old: json!("X"),
+                    new: json!("Y")
+                },
+                Change::Added {
+                    key: "C.id".to_string(),
+                    value: json!("C")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_order() {
+        let old = json!({"entries": [{"id": "A"}, {"id": "B"}, {"id": "C"}]});
+        let new = json!({"entries": [{"id": "B"}, {"id": "A"}, {"id": "D"}]});
+        assert_eq!(
+            diff(&old, &new),
+            vec![
+                Change::Reordered {
+                    key: "entries".to_string(),
+                    old: vec!["A".to_string(), "B".to_string()],
+                    new: vec!["B".to_string(), "A".to_string()],
+                },
+                Change::Removed {
+                    key: "entries.C.id".to_string(),
+                    value: json!("C")
+                },
+                Change::Added {
+                    key: "entries.D.id".to_string(),
+                    value: json!("D")
+                },
+            ]
+        );
+        // inserting doesn't change order of others
+        let new = json!({"entries": [{"id": "A"}, {"id": "D"}, {"id": "B"}, {"id": "C"}]});
+        assert_eq!(diff(&old, &new).len(), 1);
+    }
+}
@@ -0,0 +1,290 @@
+//! Discovery of game's data directory in Steam libraries and Heroic (Epic)
+//! installs
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, bail};
+use serde_json::Value;
+use tracing::{debug, warn};
+
+use crate::{profile::STEAM_APP_ID, vdf};
+
+/// Environment variable with path to game or its data directory
+pub const DATA_DIR_ENV: &str = "OW_DATA_DIR";
+
+/// Default install directory in Steam library
+const GAME_DIR: &str = "Outer Wilds";
+/// Title of game in Heroic's list of installed Epic games
+const GAME_TITLE: &str = "Outer Wilds";
+const DATA_DIR: &str = "OuterWilds_Data";
+
+/// Steam roots, relative to home directory
+const STEAM_ROOTS: &[&str] = &[
+    ".local/share/Steam",
+    ".steam/steam",
+    ".steam/root",
+    // flatpak
+    ".var/app/com.valvesoftware.Steam/.local/share/Steam",
+    ".var/app/com.valvesoftware.Steam/data/Steam",
+    // snap
+    "snap/steam/common/.local/share/Steam",
+    // macos
+    "Library/Application Support/Steam",
+];
+
+/// Heroic config directories, relative to home directory
+const HEROIC_CONFIGS: &[&str] = &[
+    ".config/heroic",
+    // flatpak
+    ".var/app/com.heroicgameslauncher.hgl/config/heroic",
+    // macos
+    "Library/Application Support/heroic",
+];
+
+/// List of Epic games, installed by Heroic, relative to its config directory
+const HEROIC_EPIC_INSTALLED: &str = "legendaryConfig/legendary/installed.json";
+
+/// Default Heroic install directories of game, relative to home directory
+const HEROIC_GAME_DIRS: &[&str] = &["Games/Heroic/OuterWilds", "Games/Heroic/Outer Wilds"];
+
+/// Find data directory: from environment variable, in Steam libraries or in
+/// Heroic installs
+pub fn find_data_dir() -> Result<PathBuf> {
+    find_data_dir_in(
+        std::env::var_os(DATA_DIR_ENV).map(PathBuf::from),
+        dirs::home_dir(),
+    )
+}
+
+fn find_data_dir_in(env_dir: Option<PathBuf>, home: Option<PathBuf>) -> Result<PathBuf> {
+    // explicitly set path should not be replaced by other install
+    if let Some(dir) = env_dir {
+        return match resolve_data_dir(&dir) {
+            Some(data_dir) => Ok(data_dir),
+            None => bail!(
+                "game data dir not found in {} (from ${DATA_DIR_ENV})",
+                dir.display()
+            ),
+        };
+    }
+
+    let mut candidates = vec![];
+    if let Some(home) = home {
+        for root in STEAM_ROOTS.iter().map(|r| home.join(r)) {
+            for library in steam_libraries(&root) {
+                let source = format!("steam library {}", library.display());
+                candidates.push((game_dir(&library), source));
+            }
+        }
+        for config in HEROIC_CONFIGS.iter().map(|c| home.join(c)) {
+            let installed = config.join(HEROIC_EPIC_INSTALLED);
+            for path in heroic_installs(&installed) {
+                candidates.push((path, installed.display().to_string()));
+            }
+        }
+        for dir in HEROIC_GAME_DIRS {
+            candidates.push((home.join(dir), "heroic default".to_string()));
+        }
+    }
+
+    let mut checked = vec![];
+    for (path, source) in candidates {
+        if checked.iter().any(|(p, _)| p == &path) {
+            continue;
+        }
+        debug!("checking {} from {source}", path.display());
+        if let Some(dir) = resolve_data_dir(&path) {
+            return Ok(dir);
+        }
+        checked.push((path, source));
+    }
+
+    let mut msg = format!("game data dir not found, pass --data-dir or set {DATA_DIR_ENV}");
+    if checked.is_empty() {
+        msg.push_str(". No Steam libraries or Heroic installs found");
+    } else {
+        msg.push_str(". Checked:");
+        for (path, source) in checked {
+            msg.push_str(&format!("\n  {} (from {source})", path.display()));
+        }
+    }
+    bail!(msg)
+}
+
+/// Accept both game directory and its `*_Data` directory. Returns `None` if
+/// there is no data directory
+pub fn resolve_data_dir(path: &Path) -> Option<PathBuf> {
+    if !path.is_dir() {
+        return None;
+    }
+    if path
+        .file_name()
+        .is_some_and(|n| n.to_string_lossy().ends_with("_Data"))
+    {
+        return Some(path.to_path_buf());
+    }
+    let default = path.join(DATA_DIR);
+    if default.is_dir() {
+        return Some(default);
+    }
+    let mut dirs: Vec<_> = std::fs::read_dir(path)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.is_dir()
+                && p.file_name()
+                    .is_some_and(|n| n.to_string_lossy().ends_with("_Data"))
+        })
+        .collect();
+    dirs.sort();
+    dirs.into_iter().next()
+}
+
+/// Root and all libraries listed in its `libraryfolders.vdf`
+fn steam_libraries(root: &Path) -> Vec<PathBuf> {
+    if !root.is_dir() {
+        return vec![];
+    }
+    let mut libraries = vec![root.to_path_buf()];
+    let file = root.join("steamapps/libraryfolders.vdf");
+    let Ok(data) = std::fs::read_to_string(&file) else {
+        return libraries;
+    };
+    match vdf::parse(&data) {
+        Ok(value) => {
+            let folders = value.get("libraryfolders").map(vdf::Value::entries);
+            for (_, folder) in folders.unwrap_or_default() {
+                // old format has path as value
+                let path = folder
+                    .as_str()
+                    .or_else(|| folder.get("path").and_then(vdf::Value::as_str));
+                if let Some(path) = path {
+                    let path = PathBuf::from(path);
+                    if !libraries.contains(&path) {
+                        libraries.push(path);
+                    }
+                }
+            }
+        }
+        Err(e) => warn!("failed to parse {}: {e}", file.display()),
+    }
+    libraries
+}
+
+/// Install paths of game in Heroic's `installed.json` of Epic games, which is
+/// map of app name to install info
+fn heroic_installs(installed: &Path) -> Vec<PathBuf> {
+    let Ok(data) = std::fs::read(installed) else {
+        return vec![];
+    };
+    let games: Value = match serde_json::from_slice(&data) {
+        Ok(games) => games,
+        Err(e) => {
+            warn!("failed to parse {}: {e}", installed.display());
+            return vec![];
+        }
+    };
+    games
+        .as_object()
+        .into_iter()
+        .flat_map(|games| games.values())
+        .filter(|game| game["title"] == GAME_TITLE)
+        .filter_map(|game| game["install_path"].as_str())
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// Game directory in library, taken from app manifest
+fn game_dir(library: &Path) -> PathBuf {
+    let steamapps = library.join("steamapps");
+    let manifest = steamapps.join(format!("appmanifest_{STEAM_APP_ID}.acf"));
+    let install_dir = std::fs::read_to_string(&manifest)
+        .ok()
+        .and_then(|data| vdf::parse(&data).ok())
+        .and_then(|v| {
+            v.get("AppState")?
+                .get("installdir")?
+                .as_str()
+                .map(str::to_string)
+        });
+    steamapps
+        .join("common")
+        .join(install_dir.as_deref().unwrap_or(GAME_DIR))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_data_dir() {
+        let tmp =
+            std::env::temp_dir().join(format!("tr-extractor-discover-{}", std::process::id()));
+        let home = tmp.join("home");
+        let root = home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam");
+        let library = tmp.join("drive");
+        std::fs::create_dir_all(root.join("steamapps")).unwrap();
+        std::fs::create_dir_all(library.join("steamapps/common/OW/OuterWilds_Data")).unwrap();
+        std::fs::write(
+            root.join("steamapps/libraryfolders.vdf"),
+            format!(
+                "\"libraryfolders\"\n{{\n\"0\" {{ \"path\" \"{}\" }}\n\"1\" {{ \"path\" \"{}\" }}\n}}",
+                root.display(),
+                library.display()
+            ),
+        )
+        .unwrap();
+        std::fs::write(
+            library.join("steamapps/appmanifest_753640.acf"),
+            "\"AppState\" { \"appid\" \"753640\" \"installdir\" \"OW\" }",
+        )
+        .unwrap();
+
+        let found = find_data_dir_in(None, Some(home.clone()));
+        // steam library exists, but env var is not ignored
+        let missing = find_data_dir_in(Some(tmp.join("missing")), Some(home.clone()));
+        let from_env = find_data_dir_in(Some(library.join("steamapps/common/OW")), None);
+        let nothing = find_data_dir_in(None, Some(tmp.join("nohome")));
+        std::fs::remove_dir_all(&tmp).unwrap();
+
+        let data_dir = library.join("steamapps/common/OW/OuterWilds_Data");
+        assert_eq!(found.unwrap(), data_dir);
+        assert_eq!(from_env.unwrap(), data_dir);
+        assert_eq!(
+            missing.unwrap_err().to_string(),
+            format!(
+                "game data dir not found in {} (from ${DATA_DIR_ENV})",
+                tmp.join("missing").display()
+            )
+        );
+        let err = nothing.unwrap_err().to_string();
+        assert!(err.contains(&format!(
+            "{} (from heroic default)",
+            tmp.join("nohome/Games/Heroic/OuterWilds").display()
+        )));
+    }
+
+    #[test]
+    fn test_find_heroic_install() {
+        let tmp = std::env::temp_dir().join(format!("tr-extractor-heroic-{}", std::process::id()));
+        let home = tmp.join("home");
+        let config = home.join(".var/app/com.heroicgameslauncher.hgl/config/heroic");
+        let game = tmp.join("games/OuterWilds");
+        std::fs::create_dir_all(config.join("legendaryConfig/legendary")).unwrap();
+        std::fs::create_dir_all(game.join("OuterWilds_Data")).unwrap();
+        std::fs::write(
+            config.join(HEROIC_EPIC_INSTALLED),
+            serde_json::json!({
+                "Other": {"title": "Other", "install_path": tmp.join("games/Other")},
+                "Owl": {"title": "Outer Wilds", "install_path": game},
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let found = find_data_dir_in(None, Some(home));
+        std::fs::remove_dir_all(&tmp).unwrap();
+        assert_eq!(found.unwrap(), game.join("OuterWilds_Data"));
+    }
+}
@@ -0,0 +1,273 @@
+//! Formats of translation files: JSON map for frontend, and formats of
+//! translation tools
+//!
+//! All formats are produced from the same map of entry id to text. English
+//! texts are used as sources in formats, which have them
+
+use std::fmt::Write;
+
+use anyhow::Result;
+use tracing::warn;
+
+use crate::{info::Lang, remap::IdTranslation};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum TranslationFormat {
+    /// Map of entry id to text
+    #[default]
+    Json,
+    /// Fluent, entry ids are used as message ids
+    Ftl,
+    /// Gettext PO, with english text as msgid and entry id as msgctxt
+    Po,
+    /// XLIFF 2.0, entry ids are used as unit ids
+    Xliff,
+}
+
+impl TranslationFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            TranslationFormat::Json => "json",
+            TranslationFormat::Ftl => "ftl",
+            TranslationFormat::Po => "po",
+            TranslationFormat::Xliff => "xlf",
+        }
+    }
+
+    /// Render translation. `english` is used for source texts
+    pub fn render(
+        self,
+        lang: Lang,
+        tr: &IdTranslation,
+        english: &IdTranslation,
+    ) -> Result<Vec<u8>> {
+        Ok(match self {
+            TranslationFormat::Json => serde_json::to_vec_pretty(tr)?,
+            TranslationFormat::Ftl => to_ftl(tr).into_bytes(),
+            TranslationFormat::Po => to_po(lang, tr, english).into_bytes(),
+            TranslationFormat::Xliff => to_xliff(lang, tr, english).into_bytes(),
+        })
+    }
+}
+
+fn to_ftl(tr: &IdTranslation) -> String {
+    let mut out = String::new();
+    for (id, text) in tr {
+        if !is_ftl_identifier(id) {
+            warn!("skipping {id}: not valid fluent identifier");
+            continue;
+        }
+        let text: String = text
+            .chars()
+            .map(|c| match c {
+                '{' | '}' => format!("{{\"{c}\"}}"),
+                c => c.to_string(),
+            })
+            .collect();
+        // not `lines`, trailing newline is a part of text
+        let mut lines = text.split('\n');
+        let first = lines.next().unwrap_or_default();
+        write!(out, "{id} = {}", escape_ftl_line(first, true)).unwrap();
+        for line in lines {
+            write!(out, "\n    {}", escape_ftl_line(line, false)).unwrap();
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn is_ftl_identifier(id: &str) -> bool {
+    let mut chars = id.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Escape leading characters, which have special meaning in fluent. Empty
+/// lines are written as empty literal, blank lines at start and end of value
+/// would be dropped
+fn escape_ftl_line(line: &str, first: bool) -> String {
+    if line.is_empty() {
+        return "{\"\"}".to_string();
+    }
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+    let mut out = String::new();
+    if !indent.is_empty() {
+        write!(out, "{{\"{indent}\"}}").unwrap();
+    }
+    match trimmed.chars().next() {
+        Some(c @ ('[' | '*' | '.')) if !first => {
+            write!(out, "{{\"{c}\"}}{}", &trimmed[1..]).unwrap();
+        }
+        _ => out.push_str(trimmed),
+    }
+    out
+}
+
+fn to_po(lang: Lang, tr: &IdTranslation, english: &IdTranslation) -> String {
+    let mut out = format!(
+        "msgid \"\"\n\
+        msgstr \"\"\n\
+        \"Language: {}\\n\"\n\
+        \"MIME-Version: 1.0\\n\"\n\
+        \"Content-Type: text/plain; charset=UTF-8\\n\"\n\
+        \"Content-Transfer-Encoding: 8bit\\n\"\n",
+        lang.code().replace('-', "_")
+    );
+    for (id, text) in tr {
+        let Some(source) = english.get(id).filter(|s| !s.is_empty()) else {
+            warn!("skipping {id}: no english text");
+            continue;
+        };
+        write!(
+            out,
+            "\nmsgctxt \"{}\"\nmsgid \"{}\"\nmsgstr \"{}\"\n",
+            escape_po(id),
+            escape_po(source),
+            escape_po(text)
+        )
+        .unwrap();
+    }
+    out
+}
+
+fn escape_po(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn to_xliff(lang: Lang, tr: &IdTranslation, english: &IdTranslation) -> String {
+    // english file contains only sources
+    let target = lang != Lang::English;
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    write!(
+        out,
+        "<xliff xmlns=\"urn:oasis:names:tc:xliff:document:2.0\" version=\"2.0\" srcLang=\"{}\"",
+        Lang::English.code()
+    )
+    .unwrap();
+    if target {
+        write!(out, " trgLang=\"{}\"", lang.code()).unwrap();
+    }
+    out.push_str(">\n  <file id=\"shiplog\">\n");
+    for (id, text) in tr {
+        let Some(source) = english.get(id).filter(|s| !s.is_empty()) else {
+            warn!("skipping {id}: no english text");
+            continue;
+        };
+        write!(
+            out,
+            "    <unit id=\"{}\">\n      <segment>\n        <source>{}</source>\n",
+            escape_xml(id),
+            escape_xml(source)
+        )
+        .unwrap();
+        if target {
+            writeln!(out, "        <target>{}</target>", escape_xml(text)).unwrap();
+        }
+        out.push_str("      </segment>\n    </unit>\n");
+    }
+    out.push_str("  </file>\n</xliff>\n");
+    out
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tr(items: &[(&str, &str)]) -> IdTranslation {
+        items
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_render() {
+        let english = tr(&[
+            ("TH_VILLAGE", "Village"),
+            ("TH_VILLAGE_X1", "Say \"hi\"\n[to] {all}"),
+        ]);
+        let russian = tr(&[("TH_VILLAGE", "Деревня"), ("TH_VILLAGE_X1", "a & <b>")]);
+
+        let render = |format: TranslationFormat, lang, tr| {
+            String::from_utf8(format.render(lang, tr, &english).unwrap()).unwrap()
+        };
+
+        assert_eq!(
+            render(TranslationFormat::Ftl, Lang::English, &english),
+            "TH_VILLAGE = Village\n\
+            TH_VILLAGE_X1 = Say \"hi\"\n    {\"[\"}to] {\"{\"}all{\"}\"}\n"
+        );
+        assert_eq!(
+            render(TranslationFormat::Po, Lang::Russian, &russian),
+            "msgid \"\"\n\
+            msgstr \"\"\n\
+            \"Language: ru\\n\"\n\
+            \"MIME-Version: 1.0\\n\"\n\
+            \"Content-Type: text/plain; charset=UTF-8\\n\"\n\
+            \"Content-Transfer-Encoding: 8bit\\n\"\n\
+            \n\
+            msgctxt \"TH_VILLAGE\"\n\
+            msgid \"Village\"\n\
+            msgstr \"Деревня\"\n\
+            \n\
+            msgctxt \"TH_VILLAGE_X1\"\n\
+            msgid \"Say \\\"hi\\\"\\n[to] {all}\"\n\
+            msgstr \"a & <b>\"\n"
+        );
+        let xliff = render(TranslationFormat::Xliff, Lang::Russian, &russian);
+        assert!(xliff.contains("srcLang=\"en\" trgLang=\"ru\""));
+        assert!(xliff.contains(
+            "<unit id=\"TH_VILLAGE_X1\">\n      <segment>\n        \
+            <source>Say &quot;hi&quot;\n[to] {all}</source>\n        \
+            <target>a &amp; &lt;b&gt;</target>\n"
+        ));
+        let xliff = render(TranslationFormat::Xliff, Lang::English, &english);
+        assert!(!xliff.contains("trgLang") && !xliff.contains("<target>"));
+    }
+
+    #[test]
+    fn test_render_ftl_empty_lines() {
+        let english = tr(&[("EMPTY", ""), ("TRAILING", "a\n"), ("BLANK", "\na\n\nb")]);
+        assert_eq!(
+            to_ftl(&english),
+            "BLANK = {\"\"}\n    a\n    {\"\"}\n    b\n\
+            EMPTY = {\"\"}\n\
+            TRAILING = a\n    {\"\"}\n"
+        );
+    }
+
+    #[test]
+    fn test_render_empty_source() {
+        let english = tr(&[("TH_VILLAGE", "Village"), ("TH_EMPTY", "")]);
+        let russian = tr(&[("TH_VILLAGE", "Деревня"), ("TH_EMPTY", "Пусто")]);
+        let po = to_po(Lang::Russian, &russian, &english);
+        let xliff = to_xliff(Lang::Russian, &russian, &english);
+        assert!(po.contains("TH_VILLAGE") && xliff.contains("TH_VILLAGE"));
+        assert!(!po.contains("TH_EMPTY") && !xliff.contains("TH_EMPTY"));
+    }
+}
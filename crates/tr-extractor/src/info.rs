@@ -1,5 +1,8 @@
+use serde::Deserialize;
+
 /// Supported language
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Lang {
     English,
     SpanishLa,
@@ -16,6 +19,21 @@ pub enum Lang {
 }
 
 impl Lang {
+    pub const ALL: &[Lang] = &[
+        Lang::English,
+        Lang::SpanishLa,
+        Lang::German,
+        Lang::French,
+        Lang::Italian,
+        Lang::Polish,
+        Lang::PortugueseBr,
+        Lang::Japanese,
+        Lang::Russian,
+        Lang::ChineseSimple,
+        Lang::Korean,
+        Lang::Turkish,
+    ];
+
     pub fn file_name(self) -> &'static str {
         match self {
             Lang::English => "english",
@@ -32,4 +50,22 @@ impl Lang {
             Lang::Turkish => "turkish",
         }
     }
+
+    /// BCP 47 language tag
+    pub fn code(self) -> &'static str {
+        match self {
+            Lang::English => "en",
+            Lang::SpanishLa => "es-419",
+            Lang::German => "de",
+            Lang::French => "fr",
+            Lang::Italian => "it",
+            Lang::Polish => "pl",
+            Lang::PortugueseBr => "pt-BR",
+            Lang::Japanese => "ja",
+            Lang::Russian => "ru",
+            Lang::ChineseSimple => "zh-Hans",
+            Lang::Korean => "ko",
+            Lang::Turkish => "tr",
+        }
+    }
 }
@@ -0,0 +1,144 @@
+//! Extract ship log entries and their translations from Outer Wilds game
+//! data
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::Path,
+};
+
+use anyhow::{Result, bail};
+use tracing::{debug, info, warn};
+
+use assets::{Assets, load_assets};
+use info::Lang;
+use library::{Library, load_library};
+use models::entries::{AstroObject, JsonEntry};
+use profile::Profile;
+use remap::{IdTranslation, Remapped, remap_translations};
+use scan::Scanner;
+use tree::{collect_fact_ids, collect_rumor_alt_names, count_entries, replace_rumor_alt_names};
+
+pub mod assets;
+pub mod detect;
+pub mod diff;
+pub mod discover;
+pub mod formats;
+pub mod info;
+pub mod library;
+pub mod models;
+pub mod output;
+pub mod pack;
+pub mod profile;
+pub mod progress;
+pub mod remap;
+pub mod richtext;
+pub mod scan;
+pub mod sprites;
+pub mod texture;
+pub mod tree;
+pub mod unity;
+pub mod validate;
+pub mod vdf;
+
+pub use discover::find_data_dir;
+
+/// Key for translation of "There's more to explore"
+pub const MORE_TO_EXPLORE_EXTRACT_KEY: &str = "973";
+pub const MORE_TO_EXPLORE_TR_KEY: &str = "MORE_TO_EXPLORE";
+
+/// Data extracted from game
+#[derive(Debug)]
+pub struct GameData {
+    pub profile: Profile,
+
+    /// Astro objects in order they are found in asset files. Entries and facts
+    /// are in order of ship log XML, as game shows them. Alternative rumor
+    /// names are replaced by their ids
+    pub astro_objects: Vec<AstroObject<JsonEntry>>,
+
+    /// Map of astro object id to name of file it was found in
+    pub sources: HashMap<String, String>,
+
+    /// Translations with Unity markup and escapes, as in game files. See
+    /// [`richtext`] for converting them
+    pub translations: HashMap<Lang, IdTranslation>,
+
+    /// Full UI tables, mapped by their keys, with Unity markup
+    pub ui_translations: HashMap<Lang, IdTranslation>,
+
+    /// Ship log library with card positions. Missing if its layout differs
+    /// from one in profile
+    pub library: Option<Library>,
+}
+
+impl GameData {
+    /// Extract data from game's data directory. If `game_version` is passed,
+    /// use profile for it instead of detecting
+    pub fn open(dir: &Path, game_version: Option<&str>) -> Result<Self> {
+        if !dir.exists() {
+            bail!("data dir \"{}\" not exists", dir.display());
+        }
+
+        let profile = profile::select_profile(dir, game_version)?;
+        info!("using profile for game version {}", profile.game_version);
+
+        let Assets {
+            mut astro_objects,
+            sources,
+            tr_objects,
+        } = load_assets(dir, &Scanner::new())?;
+
+        debug!("count of astro objects: {}", astro_objects.len());
+        debug!(
+            "count of astro entries: {}",
+            astro_objects
+                .iter()
+                .map(|a| count_entries(&a.entries))
+                .sum::<u32>()
+        );
+        debug!(
+            "count of translation entries: {}",
+            tr_objects[0].entries.len()
+        );
+
+        // alternate names for cards
+        let mut rumor_alt_names = HashMap::new();
+        for a in &astro_objects {
+            rumor_alt_names.extend(collect_rumor_alt_names(&a.entries));
+        }
+
+        let Remapped {
+            translations,
+            ui: ui_translations,
+        } = remap_translations(&astro_objects, &rumor_alt_names, tr_objects, &profile)?;
+
+        for a in &mut astro_objects {
+            replace_rumor_alt_names(&mut a.entries, &rumor_alt_names);
+        }
+
+        let library = match load_library(dir, &profile.library) {
+            Ok(library) => Some(library),
+            Err(e) => {
+                warn!("failed to load ship log library: {e:#}");
+                None
+            }
+        };
+
+        Ok(Self {
+            profile,
+            astro_objects,
+            sources,
+            translations,
+            ui_translations,
+            library,
+        })
+    }
+
+    /// Keys of facts in save file, sorted. Share links depend on this order
+    pub fn save_keys(&self) -> BTreeSet<String> {
+        self.astro_objects
+            .iter()
+            .flat_map(|a| collect_fact_ids(&a.entries))
+            .collect()
+    }
+}
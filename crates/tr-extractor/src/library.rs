@@ -0,0 +1,245 @@
+//! Ship log library: script object with per-entry data, which isn't stored in
+//! XMLs, like card positions in rumor mode
+
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use tracing::{debug, info, warn};
+
+use crate::{
+    assets::{map_asset_files, parse_asset_files},
+    profile::{FieldKind, LibraryLayout},
+    unity::{CLASS_MONO_BEHAVIOUR, CLASS_MONO_SCRIPT, PPtr, Reader, SerializedFile},
+};
+
+/// Upper bound of entries count, to fail fast on wrong layout
+const MAX_ENTRIES: usize = 10_000;
+
+#[derive(Debug)]
+pub struct Library {
+    /// Name of file library was found in
+    pub file: String,
+    pub entries: Vec<LibraryEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LibraryEntry {
+    pub id: String,
+    pub card_position: [f32; 2],
+    pub sprite: Option<AssetRef>,
+    pub alt_sprite: Option<AssetRef>,
+}
+
+/// Pointer to object, resolved to file name
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AssetRef {
+    pub file: String,
+    pub path_id: i64,
+}
+
+impl AssetRef {
+    /// Resolve pointer from object in `file`
+    pub fn resolve(file_name: &str, file: &SerializedFile, pptr: PPtr) -> Option<Self> {
+        if pptr.is_null() {
+            return None;
+        }
+        Some(Self {
+            file: file.external_name(pptr).unwrap_or(file_name).to_string(),
+            path_id: pptr.path_id,
+        })
+    }
+}
+
+/// Find ship log library in asset files of data dir
+pub fn load_library(dir: &Path, layout: &LibraryLayout) -> Result<Library> {
+    let files = map_asset_files(dir)?;
+    find_library(&parse_asset_files(&files), layout)
+}
+
+/// Find library in parsed files. Scripts can be stored in other file than
+/// their `MonoBehaviour`s, so all files are needed
+pub fn find_library(files: &[(&str, SerializedFile)], layout: &LibraryLayout) -> Result<Library> {
+    // (file name, path id) of library scripts
+    let mut scripts = vec![];
+    for (name, file) in files {
+        for object in file.objects_of(CLASS_MONO_SCRIPT) {
+            match file.mono_script_class(object) {
+                Ok(class) if class == layout.script => scripts.push((*name, object.path_id)),
+                Ok(_) => {}
+                Err(e) => debug!("failed to read script {} in {name}: {e}", object.path_id),
+            }
+        }
+    }
+    if scripts.is_empty() {
+        bail!("script {} not found", layout.script);
+    }
+
+    let mut found = vec![];
+    for (name, file) in files {
+        for object in file.objects_of(CLASS_MONO_BEHAVIOUR) {
+            let Ok((behaviour, mut r)) = file.mono_behaviour(object) else {
+                continue;
+            };
+            let script_file = file.external_name(behaviour.script).unwrap_or(name);
+            if !scripts.contains(&(script_file, behaviour.script.path_id)) {
+                continue;
+            }
+            match read_entries(&mut r, layout, name, file) {
+                Ok(entries) => found.push(Library {
+                    file: name.to_string(),
+                    entries,
+                }),
+                Err(e) => warn!("failed to read {} from {name}: {e}", layout.script),
+            }
+        }
+    }
+
+    match found.len() {
+        0 => bail!("no readable {} found", layout.script),
+        1 => {}
+        n => warn!("found {n} objects of {}, using first one", layout.script),
+    }
+    let library = found.swap_remove(0);
+    info!(
+        "extracted {} library entries from {}",
+        library.entries.len(),
+        library.file
+    );
+    Ok(library)
+}
+
+fn read_entries(
+    r: &mut Reader,
+    layout: &LibraryLayout,
+    file_name: &str,
+    file: &SerializedFile,
+) -> Result<Vec<LibraryEntry>> {
+    let mut script = Fields::default();
+    for kind in &layout.fields_before {
+        read_field(r, *kind, &mut script)?;
+    }
+
+    let count = r.i32()?;
+    if !(0..=MAX_ENTRIES as i32).contains(&count) {
+        bail!("invalid count of entries {count}, layout is probably wrong");
+    }
+    let mut entries = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let mut entry = Fields::default();
+        for kind in &layout.entry_fields {
+            read_field(r, *kind, &mut entry)?;
+        }
+        entries.push(LibraryEntry {
+            id: entry.id.context("entry without id")?.to_string(),
+            card_position: entry.card_position.context("entry without card position")?,
+            sprite: entry
+                .sprite
+                .and_then(|p| AssetRef::resolve(file_name, file, p)),
+            alt_sprite: entry
+                .alt_sprite
+                .and_then(|p| AssetRef::resolve(file_name, file, p)),
+        });
+    }
+    Ok(entries)
+}
+
+/// Fields of entry, read so far
+#[derive(Default)]
+struct Fields<'d> {
+    id: Option<&'d str>,
+    card_position: Option<[f32; 2]>,
+    sprite: Option<PPtr>,
+    alt_sprite: Option<PPtr>,
+}
+
+fn read_field<'d>(r: &mut Reader<'d>, kind: FieldKind, out: &mut Fields<'d>) -> Result<()> {
+    match kind {
+        FieldKind::Id => out.id = Some(r.aligned_str()?),
+        FieldKind::CardPosition => out.card_position = Some([r.f32()?, r.f32()?]),
+        FieldKind::Sprite => out.sprite = Some(r.pptr()?),
+        FieldKind::AltSprite => out.alt_sprite = Some(r.pptr()?),
+        FieldKind::Bool => {
+            r.u8()?;
+            r.align(4);
+        }
+        FieldKind::Int | FieldKind::Float => r.skip(4)?,
+        FieldKind::String => {
+            r.aligned_bytes()?;
+        }
+        FieldKind::Vector2 => r.skip(8)?,
+        FieldKind::PPtr => {
+            r.pptr()?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::unity::tests::{aligned, build_file, mono_behaviour, mono_script};
+
+    #[test]
+    fn test_find_library() {
+        let layout = LibraryLayout {
+            script: "ShipLogLibrary".to_string(),
+            fields_before: vec![FieldKind::Bool],
+            entry_fields: vec![
+                FieldKind::Id,
+                FieldKind::CardPosition,
+                FieldKind::Sprite,
+                FieldKind::AltSprite,
+            ],
+        };
+        let mut fields = vec![1, 0, 0, 0];
+        fields.extend_from_slice(&2i32.to_le_bytes());
+        for (id, x, y) in [
+            ("TH_VILLAGE", 10.0f32, -20.5f32),
+            ("TH_ZERO_G_CAVE", 0.0, 3.0),
+        ] {
+            fields.extend(aligned(id.as_bytes()));
+            fields.extend_from_slice(&x.to_le_bytes());
+            fields.extend_from_slice(&y.to_le_bytes());
+            // sprite from the same file and null alt sprite
+            fields.extend_from_slice(&0i32.to_le_bytes());
+            fields.extend_from_slice(&5i64.to_le_bytes());
+            fields.extend_from_slice(&[0; 12]);
+        }
+        let file = build_file(&[
+            (CLASS_MONO_SCRIPT, mono_script("OtherScript")),
+            (CLASS_MONO_SCRIPT, mono_script("ShipLogLibrary")),
+            (CLASS_MONO_BEHAVIOUR, mono_behaviour(1, "Other", &[])),
+            (
+                CLASS_MONO_BEHAVIOUR,
+                mono_behaviour(2, "ShipLogLibrary", &fields),
+            ),
+        ]);
+        let parsed = SerializedFile::parse(&file).unwrap();
+
+        let library = find_library(&[("resources.assets", parsed)], &layout).unwrap();
+        assert_eq!(library.file, "resources.assets");
+        assert_eq!(
+            library.entries,
+            vec![
+                LibraryEntry {
+                    id: "TH_VILLAGE".to_string(),
+                    card_position: [10.0, -20.5],
+                    sprite: Some(AssetRef {
+                        file: "resources.assets".to_string(),
+                        path_id: 5
+                    }),
+                    alt_sprite: None,
+                },
+                LibraryEntry {
+                    id: "TH_ZERO_G_CAVE".to_string(),
+                    card_position: [0.0, 3.0],
+                    sprite: Some(AssetRef {
+                        file: "resources.assets".to_string(),
+                        path_id: 5
+                    }),
+                    alt_sprite: None,
+                },
+            ]
+        );
+    }
+}
@@ -1,58 +1,28 @@
-use std::{
-    collections::{BTreeMap, HashMap},
-    fs::File,
-    path::PathBuf,
-};
+use std::collections::BTreeMap;
 
-use anyhow::{Context, Result, anyhow, bail};
+use anyhow::{Context, Result, bail};
 use clap::Parser;
-use heck::ToShoutySnakeCase;
-use memmap2::{Mmap, MmapOptions};
-use tracing::{Level, debug, error, info, warn};
+use tracing::{Level, debug, info, warn};
 use tracing_subscriber::FmtSubscriber;
 
-use info::Lang;
-use models::{
-    entries::{AstroObject, JsonEntry, XmlEntry},
-    translations::{Translation, Translations},
+use ow_save::{Save, keys::key_sets, link::Link};
+
+use tr_extractor::{
+    GameData,
+    discover::resolve_data_dir,
+    find_data_dir,
+    output::{
+        AUDITED_FILES, FileDiff, OutputOptions, SAVE_KEYS_FILE, check_output, check_outputs,
+        collect_outputs, read_entries, read_save_keys, write_outputs,
+    },
+    pack::{pack_files, read_dir_files},
+    progress::ShipLog,
+    sprites::extract_sprites,
+    tree::collect_fact_ids,
+    validate::{compare_save_keys, missing_translations},
 };
 
 mod args;
-mod info;
-mod models;
-
-const ASTRO_OBJECT_START: &[u8] = b"<AstroObjectEntry>";
-const ASTRO_OBJECT_END: &[u8] = b"</AstroObjectEntry>";
-const SHARED_FILE: &str = "sharedassets1.assets";
-/// Offset in file `sharedassets1.assets` for game version 1.1.16. Probably
-/// unnecessary since search seems to be pretty fast
-const V16_SHARED_OFFSET: u64 = 930000000;
-
-const TR_SHIPLOG_START: &[u8] = b"<table_shipLog>";
-const TR_SHIPLOG_END: &[u8] = b"</table_shipLog>";
-const TR_UI_START: &[u8] = b"<table_ui>";
-const TR_UI_END: &[u8] = b"</table_ui>";
-const RES_FILE: &str = "resources.assets";
-
-/// Key for translation of "There's more to explore"
-const MORE_TO_EXPLORE_EXTRACT_KEY: &str = "973";
-const MORE_TO_EXPLORE_TR_KEY: &str = "MORE_TO_EXPLORE";
-
-/// Order of `TranslationTable_XML`s in `resources.assets` for game version 1.1.16
-const V16_LANG_ORDER: &[Lang] = &[
-    Lang::SpanishLa,
-    Lang::English,
-    Lang::Turkish,
-    Lang::PortugueseBr,
-    Lang::Italian,
-    Lang::French,
-    Lang::Polish,
-    Lang::Korean,
-    Lang::ChineseSimple,
-    Lang::German,
-    Lang::Russian,
-    Lang::Japanese,
-];
 
 fn main() -> Result<()> {
     let args = args::Cli::parse();
@@ -74,551 +44,190 @@ fn main() -> Result<()> {
             .finish(),
     )?;
 
-    let dir = match args.data_dir {
-        Some(d) => d,
-        None => find_data_dir()?,
-    };
-    if !dir.exists() {
-        bail!("data dir \"{}\" not exists", dir.display());
-    }
-
-    let mut astro_objects = load_astro_objects(File::open(dir.join(SHARED_FILE))?)?;
-    let tr_objects = load_tr_objects(File::open(dir.join(RES_FILE))?)?;
-
-    debug!("count of astro objects: {}", astro_objects.len());
-    debug!(
-        "count of astro entries: {}",
-        astro_objects
-            .iter()
-            .map(|a| count_entries(&a.entries))
-            .sum::<u32>()
-    );
-    debug!("count of translation entries: {}", tr_objects[0].len());
-
-    // names and ids of astro objects for searching in translations
-    let mut astro_names_keys = HashMap::<String, Vec<String>>::with_capacity(100);
-    // alternate names for cards
-    let mut rumor_alt_names = HashMap::new();
-    for a in &astro_objects {
-        // different ids can have same name
-        for (name, id) in collect_astro_names(&a.entries) {
-            astro_names_keys
-                .entry(name)
-                .and_modify(|ids| ids.push(id.clone()))
-                .or_insert_with(|| vec![id]);
-        }
-        rumor_alt_names.extend(collect_rumor_alt_names(&a.entries));
-    }
-    debug!("count of astro names: {}", astro_names_keys.len());
-
-    // save info about astro objects
-    if args.write {
-        if !args.out_dir.exists() {
-            std::fs::create_dir(&args.out_dir).context("creating output directory")?;
-        }
-
-        for a in &mut astro_objects {
-            sort_entries(&mut a.entries);
-            replace_rumor_alt_names(&mut a.entries, &rumor_alt_names);
-        }
-
-        let output = args.out_dir.join("entries.json");
-        info!("writing {}", output.display());
-        serde_json::to_writer_pretty(File::create(output)?, &astro_objects)?;
-    }
-
-    // keys for texts
-    let mut astro_facts = HashMap::<String, Vec<String>>::with_capacity(400);
-    for a in &astro_objects {
-        // different ids can have same text
-        for (text, id) in collect_astro_texts(&a.entries) {
-            astro_facts
-                .entry(text)
-                .and_modify(|ids| ids.push(id.clone()))
-                .or_insert_with(|| vec![id]);
-        }
-    }
-    debug!("count of astro texts: {}", astro_facts.len());
-
-    // remap translations
-    let mut translations = HashMap::new();
-    let astro_names = astro_names_keys.keys().map(ToOwned::to_owned).collect();
-    for (lang, tr) in clean_translations(tr_objects, astro_names)? {
-        // BTreeMap is used for sorting keys
-        let mut translation = BTreeMap::new();
-        for (text, ids) in &astro_facts {
-            for id in ids {
-                translation.insert(
-                    id.to_owned(),
-                    tr.get(text)
-                        .expect("should have id for entry text")
-                        .to_owned(),
+    match &args.command {
+        Some(args::Command::Pack { dir, pack }) => {
+            let options = pack.options();
+            let files = read_dir_files(dir, |path| options.applies_to(path))?;
+            let files = pack_files(files, &options)?;
+            return write_outputs(dir, &files);
+        }
+        Some(args::Command::SaveLink {
+            save,
+            keys,
+            site,
+            encoding,
+        }) => {
+            let (game_version, keys) = read_save_keys(&keys.keys_dir)?;
+            if !key_sets().iter().any(|s| s.keys == keys) {
+                warn!("save keys are not known by ow-save, link can't be decoded");
+            }
+            let save = Save::open(save)?;
+            let diff = save.compare_keys(&keys);
+            if !diff.only_in_data.is_empty() || !diff.only_in_save.is_empty() {
+                warn!(
+                    "keys in save differ from keys for game version {game_version}: \
+                    {} missing, {} unknown",
+                    diff.only_in_data.len(),
+                    diff.only_in_save.len()
                 );
             }
-        }
-        for (name, ids) in &astro_names_keys {
-            for id in ids {
-                translation.insert(
-                    id.to_owned(),
-                    tr.get(name)
-                        .expect("should have name for astro object")
-                        .to_owned(),
+            println!(
+                "{}",
+                Link::from_save(&save, &keys, &game_version, *encoding)?.to_url(site)
+            );
+            return Ok(());
+        }
+        Some(args::Command::LinkFacts { link, keys }) => {
+            let (game_version, keys) = read_save_keys(&keys.keys_dir)?;
+            let decoded = Link::parse(link)?.decode(&key_sets())?;
+            if decoded.game_version != game_version {
+                info!(
+                    "link is made for game version {}, converting to {game_version}",
+                    decoded.game_version
                 );
             }
-        }
-        for (text, generated_id) in rumor_alt_names.iter() {
-            let translated = tr
-                .get(text)
-                .expect("should have translation for rumor alt name")
-                .to_owned();
-            if translation
-                .get(generated_id)
-                .is_some_and(|t| *t != translated)
-            {
-                error!("rumor alt name {generated_id} clashes with regular translation");
+            for fact in decoded.facts {
+                if keys.contains(&fact) {
+                    println!("{fact}");
+                } else {
+                    warn!("fact {fact} is not found in game version {game_version}");
+                }
             }
-            translation.insert(generated_id.to_owned(), translated);
-        }
-        translation.insert(
-            MORE_TO_EXPLORE_TR_KEY.to_owned(),
-            tr.get(MORE_TO_EXPLORE_EXTRACT_KEY)
-                .expect("should have MORE_TO_EXPLORE key")
-                .to_owned(),
-        );
-        debug!(
-            "count of keys in {} translation: {}",
-            lang.file_name(),
-            translation.len()
-        );
-
-        if args.write {
-            let output = args.out_dir.join("translations");
-            if !output.exists() {
-                std::fs::create_dir(&output).context("creating output translations directory")?;
+            return Ok(());
+        }
+        Some(args::Command::Progress {
+            save,
+            entries,
+            json,
+        }) => {
+            let astro_objects = read_entries(entries)?;
+            let save = Save::open(save)?;
+            let keys = astro_objects
+                .iter()
+                .flat_map(|a| collect_fact_ids(&a.entries))
+                .collect();
+            let diff = save.compare_keys(&keys);
+            for key in &diff.only_in_save {
+                warn!("fact {key} from save is not found in entries");
             }
-
-            let output = output.join(format!("{}.json", lang.file_name()));
-            info!("writing {}", output.display());
-            serde_json::to_writer_pretty(File::create(output)?, &translation)?;
-        }
-
-        translations.insert(lang, translation);
-    }
-
-    // validate
-    debug!("checking for missing keys in translations for entries");
-    for (lang, tr) in translations {
-        debug!("checking {}", lang.file_name());
-        for a in &astro_objects {
-            validate_entries_tr(&a.entries, &tr);
-        }
-    }
-
-    Ok(())
-}
-
-fn validate_entries_tr(entries: &[JsonEntry], tr: &BTreeMap<String, String>) {
-    for e in entries {
-        if tr.get(&e.id).is_none() {
-            warn!("missing translation for {}", e.id);
-        }
-        for f in &e.facts.explore {
-            if tr.get(&f.id).is_none() {
-                warn!("missing translation for {}", f.id);
-            }
-        }
-        for f in &e.facts.rumor {
-            if tr.get(&f.id).is_none() {
-                warn!("missing translation for {}", f.id);
+            let progress = ShipLog::new(&astro_objects, &save).progress();
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&progress)?);
+            } else {
+                print!("{progress}");
             }
+            return Ok(());
         }
-        if !e.entries.is_empty() {
-            validate_entries_tr(&e.entries, tr);
-        }
+        None => {}
     }
-}
 
-fn sort_entries(entries: &mut [JsonEntry]) {
-    entries.sort_unstable_by_key(|e| e.id.clone());
-    for e in entries {
-        sort_entries(&mut e.entries);
-    }
-}
+    let dir = match args.data_dir {
+        Some(d) => resolve_data_dir(&d)
+            .with_context(|| format!("no data dir found in \"{}\"", d.display()))?,
+        None => find_data_dir()?,
+    };
 
-fn replace_rumor_alt_names(entries: &mut [JsonEntry], name_to_key: &HashMap<String, String>) {
-    for e in entries {
-        for rumor in &mut e.facts.rumor {
-            if let Some(name) = &mut rumor.name {
-                *name = name_to_key
-                    .get(name)
-                    .expect("should have alt rumor name to key")
-                    .to_owned();
+    let data = GameData::open(&dir, args.game_version.as_deref())?;
+    for (file, count) in data
+        .sources
+        .values()
+        .fold(BTreeMap::new(), |mut acc, file| {
+            *acc.entry(file).or_insert(0) += 1;
+            acc
+        })
+    {
+        debug!("{count} astro objects from {file}");
+    }
+
+    if args.write || args.check {
+        let files = pack_files(
+            collect_outputs(
+                &data,
+                &OutputOptions {
+                    format: args.format,
+                    rich_text: args.rich_text,
+                    ui_keys: args.ui_keys.clone(),
+                },
+            )?,
+            &args.pack.options(),
+        )?;
+        if args.write {
+            for file in files
+                .iter()
+                .filter(|f| AUDITED_FILES.iter().any(|a| f.path.as_os_str() == *a))
+            {
+                if let Some(d @ FileDiff::Changed { .. }) = check_output(&args.out_dir, file)? {
+                    warn!("generated {d}");
+                }
             }
-        }
-        replace_rumor_alt_names(&mut e.entries, name_to_key);
-    }
-}
-
-fn count_entries(entries: &[JsonEntry]) -> u32 {
-    if entries.is_empty() {
-        return 0;
-    }
-    entries.len() as u32
-        + entries
-            .iter()
-            .map(|e| count_entries(&e.entries))
-            .sum::<u32>()
-}
-
-/// Clean translation keys from prefixes and map translations to keys for all
-/// languages
-///
-/// Most translation keys starts from prefix, equal to one of astro object
-/// names, e.g "VillageThe one and only Hearthian village, ...". Find them
-/// and trim
-fn clean_translations(
-    tr_objects: Vec<Vec<Translation>>,
-    astro_names: Vec<String>,
-) -> Result<HashMap<Lang, HashMap<String, String>>> {
-    let mut last_prefix = astro_names
-        .first()
-        .ok_or_else(|| anyhow!("bug: astro_names can't be empty"))?
-        .to_owned();
-
-    let mut lang_order = V16_LANG_ORDER.iter();
-    let mut translations = HashMap::new();
-    for tr_entries in tr_objects {
-        let mut translation = HashMap::new();
-        for Translation {
-            key: original,
-            value: translated,
-        } in tr_entries
-        {
-            // todo: check what "\\N" means. in some places it just has nothing in game
-            let translated = translated
-                .replace("\\n", "\n")
-                .replace("\\\n", "\n")
-                .replace("\\\\N", "");
-
-            // todo: fix this case ("Escape Pod 3" detected as prefix)
-            if original == "Escape Pod 3 Survivors" {
-                translation.insert(original, translated);
-                continue;
+            write_outputs(&args.out_dir, &files)?;
+        } else {
+            let diffs = check_outputs(&args.out_dir, &files)?;
+            for d in &diffs {
+                println!("{d}");
             }
-            if original == MORE_TO_EXPLORE_EXTRACT_KEY {
-                translation.insert(
-                    original,
-                    translated
-                        .trim_start_matches("<color=orange>")
-                        .trim_end_matches("</color>")
-                        .to_owned(),
+            if !diffs.is_empty() {
+                bail!(
+                    "{} files differ from {}",
+                    diffs.len(),
+                    args.out_dir.display()
                 );
-                continue;
-            }
-
-            if !original.starts_with(&last_prefix) {
-                let Some(prefix) = astro_names.iter().find(|&p| {
-                    // remove prefix only if key is bigger
-                    original.len() > p.len()
-                        && original.starts_with(p)
-                        // if character after prefix is not whitespace (not work)
-                        && original.chars().nth(p.len()).is_some_and(|ch| !ch.is_whitespace())
-                }) else {
-                    // rumor translation
-                    translation.insert(original, translated);
-                    continue;
-                };
-                last_prefix = prefix.to_owned();
-            }
-            translation.insert(
-                original
-                    .strip_prefix(&last_prefix)
-                    .expect("checked for prefix above")
-                    .to_string(),
-                translated,
-            );
-        }
-        translations.insert(
-            lang_order
-                .next()
-                .expect("there should be known language")
-                .to_owned(),
-            translation,
-        );
-    }
-
-    Ok(translations)
-}
-
-/// Extract info about astro objects
-fn load_astro_objects(file: File) -> Result<Vec<AstroObject<JsonEntry>>> {
-    let mmap = unsafe { MmapOptions::new().offset(V16_SHARED_OFFSET).map(&file)? };
-
-    let mut offset = 0;
-    let mut astro_objects: Vec<AstroObject<JsonEntry>> = Vec::with_capacity(100);
-    loop {
-        let astro_object = match extract_astro_object(&mmap, offset) {
-            Ok((astro_object, next_offset)) => {
-                offset = next_offset;
-                parse_astro_object(astro_object)?
-            }
-            Err(e) => match e {
-                FindError::NotFound => break,
-                FindError::Utf8Error(e) => return Err(e.into()),
-            },
-        };
-        debug!("extracted {}", astro_object.id);
-        astro_objects.push(astro_object.into());
-    }
-
-    Ok(astro_objects)
-}
-
-/// Extract translations. Currently search all shiplog translations and for
-/// [`MORE_TO_EXPLORE_KEY`]
-fn load_tr_objects(file: File) -> Result<Vec<Vec<Translation>>> {
-    let mmap = unsafe { Mmap::map(&file)? };
-
-    let mut offset = 0;
-    let mut tr_objects: Vec<Vec<Translation>> = Vec::with_capacity(100);
-    let mut is_ui_table = false;
-    let mut objects = vec![];
-    loop {
-        let extracted = if is_ui_table {
-            extract_ui_tr_object(&mmap, offset)
-        } else {
-            extract_shiplog_tr_object(&mmap, offset)
-        };
-        let tr_object = match extracted {
-            Ok((tr_object, next_offset)) => {
-                offset = next_offset;
-                parse_tr_object(tr_object).context("parsing tr object")?
             }
-            Err(e) => match e {
-                FindError::NotFound => break,
-                FindError::Utf8Error(e) => return Err(e.into()),
-            },
-        };
-        if is_ui_table {
-            objects.extend(
-                tr_object
-                    .entries
-                    .into_iter()
-                    .filter(|t| t.key == MORE_TO_EXPLORE_EXTRACT_KEY),
-            );
-            tr_objects.push(objects);
-            objects = vec![];
-        } else {
-            objects = tr_object.entries;
+            info!("all files are up to date");
         }
-
-        is_ui_table = !is_ui_table;
     }
 
-    Ok(tr_objects)
-}
-
-/// Returns map of `"Rumor alt name" -> "RUMOR_ALT_NAME"`
-fn collect_rumor_alt_names(entries: &[JsonEntry]) -> HashMap<String, String> {
-    let mut tr = HashMap::new();
-    for e in entries {
-        for rumor in &e.facts.rumor {
-            if let Some(name) = &rumor.name {
-                tr.insert(name.to_owned(), name.to_shouty_snake_case());
-            }
+    if args.sprites {
+        let report = extract_sprites(&dir, &data)?;
+        for id in &report.missing {
+            warn!("entry {id} has no sprite");
         }
-        if !e.entries.is_empty() {
-            tr.extend(collect_rumor_alt_names(&e.entries));
+        for (id, e) in &report.failed {
+            warn!("failed to extract sprite of {id}: {e:#}");
         }
+        write_outputs(&args.out_dir, &report.files)?;
     }
-    tr
-}
 
-/// Returns Vec of (text, id)
-fn collect_astro_texts(entries: &[JsonEntry]) -> Vec<(String, String)> {
-    let mut kvs = vec![];
-    for e in entries {
-        for fact in &e.facts.explore {
-            kvs.push((fact.text.clone(), fact.id.clone()));
+    if let Some(save) = &args.compare_save {
+        let diff = compare_save_keys(&data.save_keys(), save)?;
+        for key in &diff.only_in_data {
+            warn!("fact {key} is not found in save");
         }
-        for fact in &e.facts.rumor {
-            kvs.push((fact.text.clone(), fact.id.clone()));
+        for key in &diff.only_in_save {
+            warn!("fact {key} from save is not found in game data");
         }
-        if !e.entries.is_empty() {
-            kvs.extend_from_slice(&collect_astro_texts(&e.entries));
+        if diff.only_in_data.is_empty() && diff.only_in_save.is_empty() {
+            info!("save keys match keys in save");
         }
     }
-    kvs
-}
 
-/// Returns Vec of (name, id)
-fn collect_astro_names(entries: &[JsonEntry]) -> Vec<(String, String)> {
-    let mut names = vec![];
-    for e in entries {
-        names.push((e.name.clone(), e.id.clone()));
-        if !e.entries.is_empty() {
-            names.extend_from_slice(&collect_astro_names(&e.entries));
-        }
+    // validate
+    match key_sets()
+        .iter()
+        .find(|s| s.game_version == data.profile.game_version)
+    {
+        Some(set) if set.keys != data.save_keys() => {
+            warn!(
+                "save keys differ from list of game version {} in ow-save",
+                set.game_version
+            )
+        }
+        Some(_) => {}
+        None => warn!(
+            "no save keys of game version {} in ow-save, copy {SAVE_KEYS_FILE} to \
+            crates/ow-save/keys to decode share links",
+            data.profile.game_version
+        ),
     }
-    names
-}
-
-fn parse_tr_object(data: &str) -> Result<Translations> {
-    Ok(serde_xml_rs::from_str(data)?)
-}
-
-fn parse_astro_object(data: &str) -> Result<AstroObject<XmlEntry>> {
-    Ok(serde_xml_rs::from_str(data)?)
-}
-
-fn extract_shiplog_tr_object(mmap: &Mmap, offset: usize) -> Result<(&str, usize), FindError> {
-    extract_utf8(mmap, offset, TR_SHIPLOG_START, TR_SHIPLOG_END)
-}
-
-fn extract_ui_tr_object(mmap: &Mmap, offset: usize) -> Result<(&str, usize), FindError> {
-    extract_utf8(mmap, offset, TR_UI_START, TR_UI_END)
-}
-
-fn extract_astro_object(mmap: &Mmap, offset: usize) -> Result<(&str, usize), FindError> {
-    extract_utf8(mmap, offset, ASTRO_OBJECT_START, ASTRO_OBJECT_END)
-}
-
-fn extract_utf8<'m>(
-    mmap: &'m Mmap,
-    offset: usize,
-    start_marker: &[u8],
-    end_marker: &[u8],
-) -> Result<(&'m str, usize), FindError> {
-    let start = find_start_of(mmap, offset, start_marker)?;
-    let end = find_end_of(mmap, start, end_marker)?;
-
-    let object = &mmap[start..end + 1];
-    Ok((std::str::from_utf8(object)?, end + 1))
-}
-
-fn find_start_of(mmap: &Mmap, offset: usize, search: &[u8]) -> Result<usize, FindError> {
-    Ok(find_indices_of(mmap, offset, search)?.0)
-}
-
-fn find_end_of(mmap: &Mmap, offset: usize, search: &[u8]) -> Result<usize, FindError> {
-    Ok(find_indices_of(mmap, offset, search)?.1)
-}
-
-/// Search for byte substring in Mmap with offset. Returns indices of first
-/// byte and next byte after substring
-fn find_indices_of(mmap: &Mmap, offset: usize, search: &[u8]) -> Result<(usize, usize), FindError> {
-    let search_len = search.len();
-    let total_len = mmap.len();
-    for (i, &_) in mmap.iter().enumerate().skip(offset) {
-        // ....f...t
-        // ......i   - break
-        // t = total_len
-        // f = t - search_len
-        if i + search_len > total_len {
-            break;
-        }
-        if &mmap[i..i + search_len] == search {
-            return Ok((i, i + search_len - 1));
+    debug!("checking for missing keys in translations for entries");
+    for (lang, tr) in &data.translations {
+        debug!("checking {}", lang.file_name());
+        for a in &data.astro_objects {
+            for id in missing_translations(&a.entries, tr) {
+                warn!("missing translation for {id}");
+            }
         }
     }
-    Err(FindError::NotFound)
-}
-
-fn find_data_dir() -> Result<PathBuf> {
-    Ok(dirs::home_dir()
-        .ok_or_else(|| anyhow!("home dir not found"))?
-        .join(".local/share/Steam/steamapps/common/Outer Wilds/OuterWilds_Data"))
-}
 
-#[derive(Debug, thiserror::Error)]
-enum FindError {
-    #[error("marker not found")]
-    NotFound,
-
-    #[error("failed to convert bytes to utf-8 string: {0}")]
-    Utf8Error(#[from] std::str::Utf8Error),
-}
-
-#[cfg(test)]
-mod tests {
-    use models::entries::{ExploreFact, RumorFact, XmlEntry};
-
-    use super::*;
-
-    #[test]
-    fn test_parse_astro_object() {
-        let data = r#"
-<AstroObjectEntry>
-<ID>TIMBER_HEARTH</ID>
-<Entry>
-    <ID>TH_VILLAGE</ID>
-    <Name>Village</Name>
-    <Curiosity>1</Curiosity>
-    <ExploreFact>
-        <ID>TH_VILLAGE_X1</ID>
-        <Text>2</Text>
-    </ExploreFact>
-    <Entry>
-        <ID>TH_ZERO_G_CAVE</ID>
-        <Name>Zero-G Cave</Name>
-        <RumorFact>
-            <ID>TH_ZERO_G_CAVE_R1</ID>
-            <SourceID>5</SourceID>
-            <RumorName>6</RumorName>
-            <RumorNamePriority>0</RumorNamePriority>
-            <Text>3</Text>
-        </RumorFact>
-        <ExploreFact>
-            <ID>TH_ZERO_G_CAVE_X1</ID>
-            <ClueType>7</ClueType>
-            <IgnoreMoreToExplore/>
-            <Text>4</Text>
-        </ExploreFact>
-    </Entry>
-</Entry>
-</AstroObjectEntry>
-        "#;
-
-        let parsed = parse_astro_object(data).unwrap();
-        let expected = AstroObject::builder()
-            .id("TIMBER_HEARTH".to_string())
-            .entries(vec![
-                XmlEntry::builder()
-                    .id("TH_VILLAGE".to_string())
-                    .name("Village".to_string())
-                    .curiosity("1".to_string())
-                    .explore_facts(vec![
-                        ExploreFact::builder()
-                            .id("TH_VILLAGE_X1".to_string())
-                            .text("2".to_string())
-                            .build(),
-                    ])
-                    .entries(vec![
-                        XmlEntry::builder()
-                            .id("TH_ZERO_G_CAVE".to_string())
-                            .name("Zero-G Cave".to_string())
-                            .rumor_facts(vec![
-                                RumorFact::builder()
-                                    .id("TH_ZERO_G_CAVE_R1".to_string())
-                                    .source_id("5".to_string())
-                                    // .name("6".to_string())
-                                    // .name_priority(0)
-                                    .text("3".to_string())
-                                    .build(),
-                            ])
-                            .explore_facts(vec![
-                                ExploreFact::builder()
-                                    .id("TH_ZERO_G_CAVE_X1".to_string())
-                                    // .clue_type("7".to_string())
-                                    .ignore_more_to_explore(true)
-                                    .text("4".to_string())
-                                    .build(),
-                            ])
-                            .build(),
-                    ])
-                    .build(),
-            ])
-            .build();
-        similar_asserts::assert_eq!(expected, parsed);
-    }
+    Ok(())
 }
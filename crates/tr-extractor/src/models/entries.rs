@@ -9,12 +9,13 @@ where
     Entry: Debug,
     Vec<Entry>: DeserializeOwned,
 {
-    #[serde(rename(deserialize = "ID"))]
+    #[serde(rename(deserialize = "ID"), alias = "id")]
     pub id: String,
 
     #[serde(
         default,
         rename(deserialize = "Entry"),
+        alias = "entries",
         skip_serializing_if = "Vec::is_empty"
     )]
     pub entries: Vec<Entry>,
@@ -40,6 +41,18 @@ pub struct XmlEntry {
     #[cfg_attr(test, builder(default))]
     pub ignore_more_to_explore: bool,
 
+    /// Persistent condition, after setting which facts of entry are ignored
+    /// when deciding that card has more to explore
+    pub ignore_more_to_explore_condition: Option<String>,
+
+    /// Don't hide this entry when parent is not revealed
+    #[serde(default, deserialize_with = "bool_when_present")]
+    #[cfg_attr(test, builder(default))]
+    pub parent_ignore_not_revealed: bool,
+
+    /// Persistent condition, after setting which card shows alternative sprite
+    pub alt_photo_condition: Option<String>,
+
     #[serde(default, rename(deserialize = "RumorFact"))]
     #[cfg_attr(test, builder(default))]
     pub rumor_facts: Vec<RumorFact>,
@@ -57,7 +70,7 @@ pub struct JsonEntry {
     pub id: String,
 
     // serializing skipped because value should be taken by id from translation
-    #[serde(skip_serializing)]
+    #[serde(default, skip_serializing)]
     pub name: String,
 
     #[serde(default, skip_serializing_if = "std::ops::Not::not")]
@@ -70,6 +83,19 @@ pub struct JsonEntry {
     #[serde(default, skip_serializing_if = "std::ops::Not::not")]
     pub ignore_more_to_explore: bool,
 
+    /// Persistent condition, after setting which facts of entry are ignored
+    /// when deciding that card has more to explore
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ignore_more_to_explore_condition: Option<String>,
+
+    /// Don't hide this entry when parent is not revealed
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub parent_ignore_not_revealed: bool,
+
+    /// Persistent condition, after setting which card shows alternative sprite
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alt_photo_condition: Option<String>,
+
     pub facts: JsonEntryFacts,
 
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -89,11 +115,12 @@ pub struct JsonEntryFacts {
 #[cfg_attr(test, derive(PartialEq, bon::Builder))]
 #[serde(rename_all(deserialize = "PascalCase"))]
 pub struct RumorFact {
-    #[serde(rename(deserialize = "ID"))]
+    #[serde(rename(deserialize = "ID"), alias = "id")]
     pub id: String,
 
     #[serde(
         rename(deserialize = "SourceID"),
+        alias = "source_id",
         skip_serializing_if = "Option::is_none"
     )]
     pub source_id: Option<String>,
@@ -102,6 +129,7 @@ pub struct RumorFact {
     #[serde(
         default,
         deserialize_with = "bool_when_present",
+        alias = "ignore_more_to_explore",
         skip_serializing_if = "std::ops::Not::not"
     )]
     #[cfg_attr(test, builder(default))]
@@ -111,6 +139,7 @@ pub struct RumorFact {
     /// serializing, this is replaced by id
     #[serde(
         rename(deserialize = "RumorName", serialize = "name_id"),
+        alias = "name_id",
         skip_serializing_if = "Option::is_none"
     )]
     pub name: Option<String>,
@@ -118,12 +147,13 @@ pub struct RumorFact {
     /// Priority of alternative name. Bigger wins
     #[serde(
         rename(deserialize = "RumorNamePriority"),
+        alias = "name_priority",
         skip_serializing_if = "Option::is_none"
     )]
     pub name_priority: Option<u32>,
 
     // serializing skipped because value should be taken by id from translation
-    #[serde(skip_serializing)]
+    #[serde(default, skip_serializing)]
     pub text: String,
 }
 
@@ -131,23 +161,24 @@ pub struct RumorFact {
 #[cfg_attr(test, derive(PartialEq, bon::Builder))]
 #[serde(rename_all(deserialize = "PascalCase"))]
 pub struct ExploreFact {
-    #[serde(rename(deserialize = "ID"))]
+    #[serde(rename(deserialize = "ID"), alias = "id")]
     pub id: String,
 
-    // pub clue_type: Option<String>,
+    #[serde(alias = "clue_type", skip_serializing_if = "Option::is_none")]
+    pub clue_type: Option<String>,
 
-    //
     /// Ignore fact when deciding that card has more to explore
     #[serde(
         default,
         deserialize_with = "bool_when_present",
+        alias = "ignore_more_to_explore",
         skip_serializing_if = "std::ops::Not::not"
     )]
     #[cfg_attr(test, builder(default))]
     pub ignore_more_to_explore: bool,
 
     // serializing skipped because value should be taken by id from translation
-    #[serde(skip_serializing)]
+    #[serde(default, skip_serializing)]
     pub text: String,
 }
 
@@ -168,6 +199,9 @@ impl From<XmlEntry> for JsonEntry {
             curiosity: value.curiosity,
             is_curiosity: value.is_curiosity,
             ignore_more_to_explore: value.ignore_more_to_explore,
+            ignore_more_to_explore_condition: value.ignore_more_to_explore_condition,
+            parent_ignore_not_revealed: value.parent_ignore_not_revealed,
+            alt_photo_condition: value.alt_photo_condition,
             facts: JsonEntryFacts {
                 rumor: value.rumor_facts,
                 explore: value.explore_facts,
@@ -6,7 +6,7 @@ pub struct Translations {
     pub entries: Vec<Translation>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Translation {
     pub key: String,
     pub value: String,
@@ -0,0 +1,340 @@
+//! Generated files: writing them to output directory and comparing with
+//! existing ones
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, info, warn};
+
+use crate::{
+    GameData,
+    diff::{Change, diff},
+    formats::TranslationFormat,
+    info::Lang,
+    models::entries::{AstroObject, JsonEntry},
+    remap::IdTranslation,
+    richtext::{RichTextMode, render_translations},
+    tree::collect_parents,
+};
+
+/// Tree of astro objects and entries
+pub const ENTRIES_FILE: &str = "entries.json";
+/// Map of child entry id to parent entry id
+pub const PARENTS_FILE: &str = "parents.json";
+/// Sorted list of fact keys from save file
+pub const SAVE_KEYS_FILE: &str = "save_keys.json";
+/// Map of entry id to card position in rumor mode
+pub const COORDINATES_FILE: &str = "coordinates.json";
+/// Directory with UI tables from game
+pub const UI_DIR: &str = "ui";
+/// Info about extracted data
+pub const META_FILE: &str = "meta.json";
+
+/// Files, which were taken from third-party data before. Disagreements
+/// should be reported before overwriting them
+pub const AUDITED_FILES: &[&str] = &[PARENTS_FILE, COORDINATES_FILE];
+
+/// Directories, which are checked only when they exist in output directory,
+/// because site doesn't use them yet
+pub const OPTIONAL_DIRS: &[&str] = &[UI_DIR];
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Meta<'a> {
+    game_version: &'a str,
+    save_keys_count: usize,
+}
+
+/// Coordinate, written as integer when possible, like in hand-made file
+#[derive(Debug, Clone, Copy)]
+struct Coordinate(f32);
+
+impl Serialize for Coordinate {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.0.fract() == 0.0 && self.0.abs() < i32::MAX as f32 {
+            serializer.serialize_i32(self.0 as i32)
+        } else {
+            serializer.serialize_f32(self.0)
+        }
+    }
+}
+
+/// File, generated from game data
+#[derive(Debug)]
+pub struct OutputFile {
+    /// Path relative to output directory
+    pub path: PathBuf,
+    pub data: Vec<u8>,
+}
+
+/// Difference between generated and existing file
+#[derive(Debug)]
+pub enum FileDiff {
+    Missing(PathBuf),
+    Changed {
+        path: PathBuf,
+        changes: Vec<Change>,
+    },
+    /// Non-JSON file with different content
+    Content(PathBuf),
+}
+
+impl OutputFile {
+    pub fn json(path: impl Into<PathBuf>, value: &impl Serialize) -> Result<Self> {
+        Ok(Self {
+            path: path.into(),
+            data: serde_json::to_vec_pretty(value)?,
+        })
+    }
+}
+
+/// How to write translations
+#[derive(Debug, Clone, Default)]
+pub struct OutputOptions {
+    pub format: TranslationFormat,
+    pub rich_text: RichTextMode,
+    /// Keys of UI texts to write. All keys are written if not set
+    pub ui_keys: Option<Vec<String>>,
+}
+
+/// Build all output files from game data, sorted by path
+pub fn collect_outputs(data: &GameData, options: &OutputOptions) -> Result<Vec<OutputFile>> {
+    let mut files = vec![OutputFile::json(ENTRIES_FILE, &data.astro_objects)?];
+
+    let mut parents = BTreeMap::new();
+    for a in &data.astro_objects {
+        parents.extend(collect_parents(&a.entries));
+    }
+    files.push(OutputFile::json(PARENTS_FILE, &parents)?);
+
+    if let Some(library) = &data.library {
+        let coordinates: BTreeMap<_, _> = library
+            .entries
+            .iter()
+            .map(|e| (&e.id, e.card_position.map(Coordinate)))
+            .collect();
+        files.push(OutputFile::json(COORDINATES_FILE, &coordinates)?);
+    }
+
+    let save_keys = data.save_keys();
+    files.push(OutputFile::json(
+        META_FILE,
+        &Meta {
+            game_version: &data.profile.game_version,
+            save_keys_count: save_keys.len(),
+        },
+    )?);
+    files.push(OutputFile::json(SAVE_KEYS_FILE, &save_keys)?);
+
+    let OutputOptions {
+        format,
+        rich_text,
+        ui_keys,
+    } = options;
+    let translations = render_translations(&data.translations, *rich_text);
+    let english = &translations[&Lang::English];
+    for (lang, translation) in &translations {
+        files.push(OutputFile {
+            path: Path::new("translations").join(format!(
+                "{}.{}",
+                lang.file_name(),
+                format.extension()
+            )),
+            data: format.render(*lang, translation, english)?,
+        });
+    }
+
+    let ui = render_translations(&data.ui_translations, *rich_text);
+    if let Some(keys) = ui_keys
+        && let Some(english) = ui.get(&Lang::English)
+    {
+        for key in keys.iter().filter(|k| !english.contains_key(*k)) {
+            warn!("ui key {key} not found");
+        }
+    }
+    for (lang, table) in &ui {
+        files.push(OutputFile::json(
+            Path::new(UI_DIR).join(format!("{}.json", lang.file_name())),
+            &select_keys(table, ui_keys.as_deref()),
+        )?);
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// Read save keys and their game version from output directory
+pub fn read_save_keys(dir: &Path) -> Result<(String, BTreeSet<String>)> {
+    let read = |file| {
+        let path = dir.join(file);
+        std::fs::read(&path).with_context(|| format!("reading {}", path.display()))
+    };
+    let meta = read(META_FILE)?;
+    let meta: Meta = serde_json::from_slice(&meta).context("parsing meta")?;
+    let keys: BTreeSet<String> =
+        serde_json::from_slice(&read(SAVE_KEYS_FILE)?).context("parsing save keys")?;
+    if keys.len() != meta.save_keys_count {
+        bail!(
+            "found {} save keys, but meta contains {}",
+            keys.len(),
+            meta.save_keys_count
+        );
+    }
+    Ok((meta.game_version.to_string(), keys))
+}
+
+/// Read astro objects from generated entries file
+pub fn read_entries(path: &Path) -> Result<Vec<AstroObject<JsonEntry>>> {
+    let data = std::fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+    serde_json::from_slice(&data).with_context(|| format!("parsing {}", path.display()))
+}
+
+/// Subset of table with passed keys, or full table
+fn select_keys(table: &IdTranslation, keys: Option<&[String]>) -> IdTranslation {
+    match keys {
+        Some(keys) => table
+            .iter()
+            .filter(|(k, _)| keys.contains(k))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect(),
+        None => table.clone(),
+    }
+}
+
+/// Write files to output directory, creating directories when needed
+pub fn write_outputs(dir: &Path, files: &[OutputFile]) -> Result<()> {
+    for file in files {
+        let output = dir.join(&file.path);
+        if let Some(parent) = output.parent()
+            && !parent.exists()
+        {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("creating directory {}", parent.display()))?;
+        }
+        if std::fs::read(&output).is_ok_and(|data| data == file.data) {
+            debug!("{} is unchanged", output.display());
+            continue;
+        }
+        info!("writing {}", output.display());
+        std::fs::write(&output, &file.data)
+            .with_context(|| format!("writing {}", output.display()))?;
+    }
+    Ok(())
+}
+
+/// Compare files with existing files in output directory. Returns only
+/// differing files. Files in optional directories are skipped, when directory
+/// doesn't exist
+pub fn check_outputs(dir: &Path, files: &[OutputFile]) -> Result<Vec<FileDiff>> {
+    let skipped: Vec<_> = OPTIONAL_DIRS
+        .iter()
+        .filter(|d| !dir.join(d).exists())
+        .collect();
+    for d in &skipped {
+        info!("{} doesn't exist, not checking it", dir.join(d).display());
+    }
+    let mut diffs = vec![];
+    for file in files {
+        if skipped.iter().any(|d| file.path.starts_with(d)) {
+            continue;
+        }
+        diffs.extend(check_output(dir, file)?);
+    }
+    Ok(diffs)
+}
+
+/// Compare file with existing file in output directory
+pub fn check_output(dir: &Path, file: &OutputFile) -> Result<Option<FileDiff>> {
+    let existing = dir.join(&file.path);
+    if !existing.exists() {
+        return Ok(Some(FileDiff::Missing(file.path.clone())));
+    }
+    let data =
+        std::fs::read(&existing).with_context(|| format!("reading {}", existing.display()))?;
+    if file.path.extension().is_none_or(|e| e != "json") {
+        return Ok((data != file.data).then(|| FileDiff::Content(file.path.clone())));
+    }
+    let old: Value =
+        serde_json::from_slice(&data).with_context(|| format!("parsing {}", existing.display()))?;
+    let new: Value = serde_json::from_slice(&file.data)?;
+    let changes = diff(&old, &new);
+    if changes.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(FileDiff::Changed {
+        path: file.path.clone(),
+        changes,
+    }))
+}
+
+impl std::fmt::Display for FileDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FileDiff::Missing(path) => write!(f, "{}: missing", path.display()),
+            FileDiff::Content(path) => write!(f, "{}: content differs", path.display()),
+            FileDiff::Changed { path, changes } => {
+                let count = |pred: fn(&Change) -> bool| changes.iter().filter(|c| pred(c)).count();
+                write!(
+                    f,
+                    "{}: {} added, {} removed, {} changed, {} reordered",
+                    path.display(),
+                    count(|c| matches!(c, Change::Added { .. })),
+                    count(|c| matches!(c, Change::Removed { .. })),
+                    count(|c| matches!(c, Change::Changed { .. })),
+                    count(|c| matches!(c, Change::Reordered { .. })),
+                )?;
+                for c in changes {
+                    write!(f, "\n  {c}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_optional_dirs() {
+        let dir = std::env::temp_dir().join(format!("tr-extractor-check-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(META_FILE), "{}").unwrap();
+        let files = [
+            OutputFile {
+                path: META_FILE.into(),
+                data: b"{}".to_vec(),
+            },
+            OutputFile {
+                path: Path::new(UI_DIR).join("english.json"),
+                data: b"{}".to_vec(),
+            },
+        ];
+        let without_ui = check_outputs(&dir, &files).unwrap();
+        std::fs::create_dir_all(dir.join(UI_DIR)).unwrap();
+        let with_ui = check_outputs(&dir, &files).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(without_ui.is_empty());
+        assert!(matches!(with_ui.as_slice(), [FileDiff::Missing(p)] if p == &files[1].path));
+    }
+
+    #[test]
+    fn test_select_keys() {
+        let table: IdTranslation = [("1", "Ship Log"), ("973", "more"), ("1000", "Map")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        assert_eq!(select_keys(&table, None), table);
+        let keys = ["973".to_string(), "missing".to_string()];
+        let selected = select_keys(&table, Some(&keys));
+        assert_eq!(
+            selected.into_iter().collect::<Vec<_>>(),
+            [("973".to_string(), "more".to_string())]
+        );
+    }
+}
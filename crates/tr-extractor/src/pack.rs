@@ -0,0 +1,229 @@
+//! Preparing files for deploy: minifying JSON and writing pre-compressed
+//! siblings, which can be served instead of original files
+
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use tracing::info;
+
+use crate::output::OutputFile;
+
+/// Files with these extensions are already compressed
+const COMPRESSED_EXTENSIONS: &[&str] = &["gz", "br", "jpg", "jpeg", "png", "webp"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Compression {
+    Gz,
+    Br,
+}
+
+impl Compression {
+    const ALL: &[Compression] = &[Compression::Gz, Compression::Br];
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Compression::Gz => "gz",
+            Compression::Br => "br",
+        }
+    }
+
+    pub fn compress(self, data: &[u8]) -> Result<Vec<u8>> {
+        let mut out = vec![];
+        match self {
+            Compression::Gz => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(&mut out, flate2::Compression::best());
+                encoder.write_all(data)?;
+                encoder.finish()?;
+            }
+            Compression::Br => {
+                let mut encoder = brotli::CompressorWriter::new(&mut out, 4096, 11, 22);
+                encoder.write_all(data)?;
+                // finishes stream
+                encoder.into_inner();
+            }
+        }
+        Ok(out)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PackOptions {
+    /// Remove whitespace from JSON files
+    pub compact: bool,
+
+    /// Write compressed siblings of each file
+    pub compress: Vec<Compression>,
+}
+
+impl PackOptions {
+    /// Whether packing changes file or adds siblings to it
+    pub fn applies_to(&self, path: &Path) -> bool {
+        (self.compact && has_extension(path, "json"))
+            || (!self.compress.is_empty() && !is_compressed(path))
+    }
+}
+
+/// Minify JSON files and add compressed siblings
+pub fn pack_files(files: Vec<OutputFile>, options: &PackOptions) -> Result<Vec<OutputFile>> {
+    let mut packed = Vec::with_capacity(files.len() * (options.compress.len() + 1));
+    for mut file in files {
+        if options.compact && has_extension(&file.path, "json") {
+            file.data = minify_json(&file.data);
+        }
+        if !is_compressed(&file.path) {
+            for c in &options.compress {
+                let mut path = file.path.clone().into_os_string();
+                path.push(".");
+                path.push(c.extension());
+                packed.push(OutputFile {
+                    path: path.into(),
+                    data: c
+                        .compress(&file.data)
+                        .with_context(|| format!("compressing {}", file.path.display()))?,
+                });
+            }
+        }
+        packed.push(file);
+    }
+    packed.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(packed)
+}
+
+/// Read files in directory recursively, matching filter, except compressed
+/// siblings. Paths are relative to `dir`
+pub fn read_dir_files(dir: &Path, filter: impl Fn(&Path) -> bool) -> Result<Vec<OutputFile>> {
+    let mut files = vec![];
+    let mut dirs = vec![PathBuf::new()];
+    while let Some(rel) = dirs.pop() {
+        let full = dir.join(&rel);
+        for entry in
+            std::fs::read_dir(&full).with_context(|| format!("reading {}", full.display()))?
+        {
+            let entry = entry?;
+            let path = rel.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                dirs.push(path);
+            } else if filter(&path)
+                && !Compression::ALL
+                    .iter()
+                    .any(|c| has_extension(&path, c.extension()))
+            {
+                let data = std::fs::read(entry.path())
+                    .with_context(|| format!("reading {}", entry.path().display()))?;
+                files.push(OutputFile { path, data });
+            }
+        }
+    }
+    info!("found {} files in {}", files.len(), dir.display());
+    Ok(files)
+}
+
+fn is_compressed(path: &Path) -> bool {
+    COMPRESSED_EXTENSIONS.iter().any(|e| has_extension(path, e))
+}
+
+fn has_extension(path: &Path, ext: &str) -> bool {
+    path.extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case(ext))
+}
+
+/// Remove whitespace outside of strings. Unlike reserializing, this keeps
+/// order of keys and formatting of numbers
+pub fn minify_json(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    let (mut in_string, mut escaped) = (false, false);
+    for &b in data {
+        if in_string {
+            match b {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+        } else if b == b'"' {
+            in_string = true;
+        } else if b.is_ascii_whitespace() {
+            continue;
+        }
+        out.push(b);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+
+    #[test]
+    fn test_minify_json() {
+        let data = b"{\n  \"a b\": [1, 2.50],\n  \"c\": \"\\\" x \\\\\"\n}\n";
+        assert_eq!(
+            minify_json(data),
+            b"{\"a b\":[1,2.50],\"c\":\"\\\" x \\\\\"}".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_pack_files() {
+        let files = vec![
+            OutputFile {
+                path: "entries.json".into(),
+                data: b"{ \"a\": 1 }".to_vec(),
+            },
+            OutputFile {
+                path: "sprites/A.jpg".into(),
+                data: vec![0xff, 0xd8],
+            },
+        ];
+        let options = PackOptions {
+            compact: true,
+            compress: vec![Compression::Gz, Compression::Br],
+        };
+        let packed = pack_files(files, &options).unwrap();
+        let paths: Vec<_> = packed.iter().map(|f| f.path.to_str().unwrap()).collect();
+        assert_eq!(
+            paths,
+            [
+                "entries.json",
+                "entries.json.br",
+                "entries.json.gz",
+                "sprites/A.jpg"
+            ]
+        );
+        assert_eq!(packed[0].data, b"{\"a\":1}");
+
+        let mut data = vec![];
+        flate2::read::GzDecoder::new(packed[2].data.as_slice())
+            .read_to_end(&mut data)
+            .unwrap();
+        assert_eq!(data, b"{\"a\":1}");
+        let mut data = vec![];
+        brotli::Decompressor::new(packed[1].data.as_slice(), 4096)
+            .read_to_end(&mut data)
+            .unwrap();
+        assert_eq!(data, b"{\"a\":1}");
+    }
+
+    #[test]
+    fn test_applies_to() {
+        let compact = PackOptions {
+            compact: true,
+            compress: vec![],
+        };
+        assert!(compact.applies_to(Path::new("entries.json")));
+        assert!(!compact.applies_to(Path::new("fonts/font.woff2")));
+        assert!(!compact.applies_to(Path::new("sprites/A.jpg")));
+        let compress = PackOptions {
+            compact: false,
+            compress: vec![Compression::Gz],
+        };
+        assert!(compress.applies_to(Path::new("fonts/font.woff2")));
+        assert!(!compress.applies_to(Path::new("sprites/A.jpg")));
+    }
+}
@@ -0,0 +1,305 @@
+//! Per-version extraction profiles. Profiles are stored as JSON files in
+//! `profiles/` directory of this crate and embedded into binary, so adding a
+//! new game version only requires adding a new file there
+
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use tracing::{debug, info, warn};
+
+use crate::{info::Lang, unity::SerializedFile, vdf};
+
+/// Steam ID of Outer Wilds
+pub const STEAM_APP_ID: u32 = 753640;
+
+const GAME_MANAGERS_FILE: &str = "globalgamemanagers";
+
+const PROFILES: &[&str] = &[include_str!("../profiles/1.1.16.json")];
+
+/// Extraction settings for specific game version
+#[derive(Debug, Deserialize)]
+pub struct Profile {
+    pub game_version: String,
+
+    /// Version of Unity game is built with, e.g. `2019.4.39f1`
+    pub unity_version: String,
+
+    /// Steam build IDs of this game version, used to distinguish versions
+    /// built with the same Unity version
+    #[serde(default)]
+    pub steam_build_ids: Vec<u32>,
+
+    /// Order of `TranslationTable_XML`s in `resources.assets`. Languages are
+    /// detected by content, this is used to notice reordering in patches
+    pub lang_order: Vec<Lang>,
+
+    /// Layout of ship log library, which stores card positions
+    pub library: LibraryLayout,
+}
+
+/// Layout of ship log library script. Release builds don't have type trees,
+/// so fields of serialized script should be described here
+#[derive(Debug, Deserialize)]
+pub struct LibraryLayout {
+    /// Class name of script
+    pub script: String,
+
+    /// Fields of script before array of entries
+    #[serde(default)]
+    pub fields_before: Vec<FieldKind>,
+
+    /// Fields of each entry
+    pub entry_fields: Vec<FieldKind>,
+}
+
+/// Serialized field of script. Known fields are read, others are skipped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldKind {
+    /// Entry id string
+    Id,
+    /// Card position in rumor mode, `Vector2`
+    CardPosition,
+    /// Pointer to card's `Sprite`
+    Sprite,
+    /// Pointer to `Sprite`, shown instead of main one under some condition
+    AltSprite,
+    Bool,
+    Int,
+    Float,
+    String,
+    Vector2,
+    /// Pointer to object
+    #[serde(rename = "pptr")]
+    PPtr,
+}
+
+/// Version info read from game files
+#[derive(Debug, Default)]
+pub struct DetectedVersion {
+    pub unity_version: Option<String>,
+    pub steam_build_id: Option<u32>,
+}
+
+impl std::fmt::Display for DetectedVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unity {}, steam build {}",
+            self.unity_version.as_deref().unwrap_or("unknown"),
+            self.steam_build_id
+                .map(|id| id.to_string())
+                .as_deref()
+                .unwrap_or("unknown"),
+        )
+    }
+}
+
+/// Load all known profiles
+pub fn profiles() -> Result<Vec<Profile>> {
+    PROFILES
+        .iter()
+        .map(|p| {
+            let profile: Profile = serde_json::from_str(p).context("parsing embedded profile")?;
+            profile.validate()?;
+            Ok(profile)
+        })
+        .collect()
+}
+
+impl Profile {
+    fn validate(&self) -> Result<()> {
+        for field in [FieldKind::Id, FieldKind::CardPosition] {
+            if !self.library.entry_fields.contains(&field) {
+                bail!(
+                    "profile {}: library entry fields should contain {field:?}",
+                    self.game_version
+                );
+            }
+        }
+        for lang in Lang::ALL {
+            let count = self.lang_order.iter().filter(|&l| l == lang).count();
+            if count != 1 {
+                bail!(
+                    "profile {}: language {} should be listed once, found {count}",
+                    self.game_version,
+                    lang.file_name()
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Select profile for game in data dir. If `game_version` is passed, select
+/// profile by it instead of detecting
+pub fn select_profile(data_dir: &Path, game_version: Option<&str>) -> Result<Profile> {
+    let profiles = profiles()?;
+    let known = || {
+        profiles
+            .iter()
+            .map(|p| p.game_version.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    if let Some(version) = game_version {
+        let known = known();
+        return profiles
+            .into_iter()
+            .find(|p| p.game_version == version)
+            .with_context(|| format!("unknown game version {version}, known versions: {known}"));
+    }
+
+    let detected = detect_version(data_dir)?;
+    info!("detected {detected}");
+    find_profile(profiles, &detected)
+}
+
+/// Select profile matching detected version
+fn find_profile(profiles: Vec<Profile>, detected: &DetectedVersion) -> Result<Profile> {
+    let known = || {
+        profiles
+            .iter()
+            .map(|p| p.game_version.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let Some(unity_version) = &detected.unity_version else {
+        bail!("failed to detect game version, pass it with --game-version");
+    };
+    let mut candidates: Vec<_> = profiles
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| &p.unity_version == unity_version)
+        .collect();
+    // patches are built with the same Unity version, so only build id tells
+    // that version is new
+    if let Some(build_id) = detected.steam_build_id {
+        let known_build = candidates
+            .iter()
+            .any(|(_, p)| p.steam_build_ids.contains(&build_id));
+        if known_build {
+            candidates.retain(|(_, p)| p.steam_build_ids.contains(&build_id));
+        } else if let [(_, p)] = candidates.as_slice()
+            && p.steam_build_ids.is_empty()
+        {
+            // profile without build ids can't tell a patch apart
+            warn!(
+                "Steam build {build_id} isn't listed by any profile, using {} with the same Unity version",
+                p.game_version
+            );
+        } else {
+            bail!(
+                "unknown Steam build {build_id} ({detected}), known versions: {}. Add build id to `steam_build_ids` of profile, if game data didn't change, or pass --game-version",
+                known()
+            );
+        }
+    }
+
+    let index = match candidates.as_slice() {
+        [(i, _)] => *i,
+        [] => bail!(
+            "unknown game version ({detected}), known versions: {}",
+            known()
+        ),
+        _ => bail!(
+            "can't decide game version ({detected}), candidates: {}. Pass it with --game-version",
+            candidates
+                .iter()
+                .map(|(_, p)| p.game_version.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    };
+    Ok(profiles.into_iter().nth(index).expect("index is valid"))
+}
+
+/// Read unity version from `globalgamemanagers` and build ID from Steam app
+/// manifest
+pub fn detect_version(data_dir: &Path) -> Result<DetectedVersion> {
+    let mut detected = DetectedVersion::default();
+
+    let managers = data_dir.join(GAME_MANAGERS_FILE);
+    match std::fs::read(&managers) {
+        Ok(data) => match SerializedFile::parse(&data) {
+            Ok(file) => detected.unity_version = Some(file.unity_version),
+            Err(e) => warn!("failed to read {}: {e}", managers.display()),
+        },
+        Err(e) => warn!("failed to open {}: {e}", managers.display()),
+    }
+
+    // <library>/steamapps/common/Outer Wilds/OuterWilds_Data
+    let manifest = data_dir
+        .ancestors()
+        .nth(3)
+        .map(|steamapps| steamapps.join(format!("appmanifest_{STEAM_APP_ID}.acf")));
+    if let Some(manifest) = manifest.filter(|m| m.exists()) {
+        debug!("reading {}", manifest.display());
+        let data = std::fs::read_to_string(&manifest)
+            .with_context(|| format!("reading {}", manifest.display()))?;
+        detected.steam_build_id = parse_build_id(&data);
+    }
+
+    Ok(detected)
+}
+
+/// Find `"buildid"` in Steam app manifest
+fn parse_build_id(manifest: &str) -> Option<u32> {
+    vdf::parse(manifest)
+        .ok()?
+        .get("AppState")?
+        .get("buildid")?
+        .as_str()?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiles_valid() {
+        profiles().unwrap();
+    }
+
+    #[test]
+    fn test_find_profile() {
+        let detected = |build_id| DetectedVersion {
+            unity_version: Some("2019.4.39f1".to_string()),
+            steam_build_id: build_id,
+        };
+        // embedded profiles as is
+        let profile = find_profile(profiles().unwrap(), &detected(Some(14914367))).unwrap();
+        assert_eq!(profile.game_version, "1.1.16");
+
+        let profiles = || {
+            let mut profiles = profiles().unwrap();
+            profiles[0].steam_build_ids = vec![100];
+            profiles
+        };
+        let profile = find_profile(profiles(), &detected(Some(100))).unwrap();
+        assert_eq!(profile.game_version, "1.1.16");
+        // not from Steam
+        find_profile(profiles(), &detected(None)).unwrap();
+        // patch with the same unity version
+        let err = find_profile(profiles(), &detected(Some(1))).unwrap_err();
+        assert!(err.to_string().starts_with("unknown Steam build 1"));
+    }
+
+    #[test]
+    fn test_parse_build_id() {
+        let manifest = r#"
+"AppState"
+{
+	"appid"		"753640"
+	"buildid"		"14914367"
+	"LastOwner"		"0"
+}
+"#;
+        assert_eq!(parse_build_id(manifest), Some(14914367));
+    }
+}
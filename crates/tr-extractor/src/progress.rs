@@ -0,0 +1,520 @@
+//! Exploration progress of save: revealed facts and states of cards
+//!
+//! Rules for what is shown on cards. Marked ones are not on site and are not
+//! verified against the game yet, there are no real saves with what ship log
+//! shows for them:
+//! - card is explored when any of its explore facts is revealed, and rumor
+//!   only when only rumor facts are revealed
+//! - (unverified) child card is hidden while its parent card is hidden, unless
+//!   child has `parent_ignore_not_revealed`
+//! - explored card has more to explore, when it has hidden explore fact, or
+//!   hidden rumor leads from it to card, which is not explored yet, or
+//!   (unverified) any of its child cards has more to explore
+//! - facts and rumors with `ignore_more_to_explore` are skipped. In DLC it's
+//!   set on rumors, and then card, which is source of rumor, isn't marked
+//! - entry with `ignore_more_to_explore`, or (unverified) with its
+//!   `ignore_more_to_explore_condition` set in save, never has more to
+//!   explore, its child cards don't mark it either
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt::Display,
+};
+
+use ow_save::Save;
+use serde::Serialize;
+
+use crate::models::entries::{AstroObject, JsonEntry, RumorFact};
+
+/// Curiosity of entries without explicit one, same as on site
+pub const OTHER_CURIOSITY: &str = "OTHER";
+
+/// State of card in ship log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CardState {
+    /// No facts revealed
+    Hidden,
+    /// Only rumor facts revealed, card is shown with "?" instead of image
+    RumorOnly,
+    /// At least one explore fact revealed
+    Explored,
+}
+
+/// Entries of all astro objects with facts, revealed in save
+pub struct ShipLog<'a> {
+    /// Entries with ids of their astro objects, parents before children
+    entries: Vec<(&'a str, &'a JsonEntry)>,
+    /// Map of entry id to its parent entry
+    parents: HashMap<&'a str, &'a JsonEntry>,
+    /// Map of entry id to rumors, which have this entry as source, with entry
+    /// they lead to
+    rumors_from: HashMap<&'a str, Vec<(&'a JsonEntry, &'a RumorFact)>>,
+    opened: BTreeSet<&'a str>,
+    conditions: &'a BTreeMap<String, bool>,
+}
+
+impl<'a> ShipLog<'a> {
+    pub fn new(astro_objects: &'a [AstroObject<JsonEntry>], save: &'a Save) -> Self {
+        let mut entries = vec![];
+        let mut parents = HashMap::new();
+        for a in astro_objects {
+            flatten(&a.id, &a.entries, &mut entries);
+        }
+        for (_, e) in &entries {
+            for child in &e.entries {
+                parents.insert(child.id.as_str(), *e);
+            }
+        }
+        let mut rumors_from = HashMap::<_, Vec<_>>::new();
+        for (_, e) in &entries {
+            for rumor in &e.facts.rumor {
+                if let Some(source) = &rumor.source_id {
+                    rumors_from
+                        .entry(source.as_str())
+                        .or_default()
+                        .push((*e, rumor));
+                }
+            }
+        }
+        Self {
+            entries,
+            parents,
+            rumors_from,
+            opened: save.opened_facts(),
+            conditions: &save.persistent_conditions,
+        }
+    }
+
+    pub fn is_revealed(&self, fact_id: &str) -> bool {
+        self.opened.contains(fact_id)
+    }
+
+    /// State of card by its own facts, see [`Self::card_state`] for state
+    /// shown on map
+    pub fn state(&self, entry: &JsonEntry) -> CardState {
+        if entry.facts.explore.iter().any(|f| self.is_revealed(&f.id)) {
+            CardState::Explored
+        } else if entry.facts.rumor.iter().any(|f| self.is_revealed(&f.id)) {
+            CardState::RumorOnly
+        } else {
+            CardState::Hidden
+        }
+    }
+
+    /// State of card on map: hidden while parent card is hidden
+    pub fn card_state(&self, entry: &JsonEntry) -> CardState {
+        if !entry.parent_ignore_not_revealed
+            && let Some(parent) = self.parents.get(entry.id.as_str())
+            && self.card_state(parent) == CardState::Hidden
+        {
+            return CardState::Hidden;
+        }
+        self.state(entry)
+    }
+
+    /// Entry is ignored by its flag or by persistent condition in save
+    fn is_ignored(&self, entry: &JsonEntry) -> bool {
+        entry.ignore_more_to_explore
+            || entry
+                .ignore_more_to_explore_condition
+                .as_ref()
+                .is_some_and(|c| self.conditions.get(c) == Some(&true))
+    }
+
+    /// Card is explored, but has hidden explore facts, rumors leading from it
+    /// to unexplored cards, or child cards with more to explore. Ignored
+    /// entries and facts are skipped
+    pub fn has_more_to_explore(&self, entry: &JsonEntry) -> bool {
+        if self.card_state(entry) != CardState::Explored || self.is_ignored(entry) {
+            return false;
+        }
+        let hidden_fact = entry
+            .facts
+            .explore
+            .iter()
+            .any(|f| !f.ignore_more_to_explore && !self.is_revealed(&f.id));
+        let hidden_rumor = self
+            .rumors_from
+            .get(entry.id.as_str())
+            .into_iter()
+            .flatten()
+            .any(|(target, rumor)| {
+                !rumor.ignore_more_to_explore
+                    && !self.is_revealed(&rumor.id)
+                    && self.card_state(target) != CardState::Explored
+            });
+        let child_more = entry.entries.iter().any(|c| self.has_more_to_explore(c));
+        hidden_fact || hidden_rumor || child_more
+    }
+
+    /// Revealed rumors with source, grouped by pair of entries they join.
+    /// Only pairs with several rumors are kept, they are drawn as one arrow
+    pub fn joined_rumors(&self) -> BTreeMap<String, JoinedRumors> {
+        let mut joined = BTreeMap::<String, JoinedRumors>::new();
+        for (_, e) in &self.entries {
+            for rumor in &e.facts.rumor {
+                let Some(source) = &rumor.source_id else {
+                    continue;
+                };
+                if !self.is_revealed(&rumor.id) {
+                    continue;
+                }
+                let mut entries = [e.id.clone(), source.clone()];
+                entries.sort();
+                joined
+                    .entry(entries.join(","))
+                    .or_insert_with(|| JoinedRumors {
+                        entries,
+                        rumors: vec![],
+                    })
+                    .rumors
+                    .push(rumor.id.clone());
+            }
+        }
+        joined.retain(|_, j| j.rumors.len() > 1);
+        joined
+    }
+
+    /// Alternative name of rumor only card: name of revealed rumor with
+    /// biggest priority, first one when priorities are equal
+    pub fn alt_name<'e>(&self, entry: &'e JsonEntry) -> Option<&'e str> {
+        if self.card_state(entry) != CardState::RumorOnly {
+            return None;
+        }
+        let mut alt_name = None;
+        let mut last_priority = None;
+        for rumor in &entry.facts.rumor {
+            let Some(name) = &rumor.name else {
+                continue;
+            };
+            // not all rumors with name have priority
+            let priority = rumor.name_priority.unwrap_or(0);
+            if self.is_revealed(&rumor.id) && last_priority.is_none_or(|p| priority > p) {
+                alt_name = Some(name.as_str());
+                last_priority = Some(priority);
+            }
+        }
+        alt_name
+    }
+
+    pub fn progress(&self) -> Progress {
+        let mut progress = Progress::default();
+        for (astro_object, e) in &self.entries {
+            let curiosity = e.curiosity.as_deref().unwrap_or(OTHER_CURIOSITY);
+            let facts = e.facts.explore.len() + e.facts.rumor.len();
+            let revealed = e
+                .facts
+                .explore
+                .iter()
+                .map(|f| &f.id)
+                .chain(e.facts.rumor.iter().map(|f| &f.id))
+                .filter(|id| self.is_revealed(id))
+                .count();
+            let state = self.card_state(e);
+            let more_to_explore = self.has_more_to_explore(e);
+
+            for stats in [
+                &mut progress.overall,
+                progress
+                    .astro_objects
+                    .entry(astro_object.to_string())
+                    .or_default(),
+                progress
+                    .curiosities
+                    .entry(curiosity.to_string())
+                    .or_default(),
+            ] {
+                stats.facts_revealed += revealed;
+                stats.facts_total += facts;
+                stats.cards_total += 1;
+                if state != CardState::Hidden {
+                    stats.cards_found += 1;
+                }
+                if state == CardState::RumorOnly {
+                    stats.rumor_only += 1;
+                }
+                if more_to_explore {
+                    stats.more_to_explore += 1;
+                }
+            }
+            if state == CardState::RumorOnly {
+                progress.rumor_only_cards.push(e.id.clone());
+            }
+            if more_to_explore {
+                progress.more_to_explore_cards.push(e.id.clone());
+            }
+            if let Some(name) = self.alt_name(e) {
+                progress.alt_names.insert(e.id.clone(), name.to_string());
+            }
+        }
+        progress.joined_rumors = self.joined_rumors();
+        progress
+    }
+}
+
+fn flatten<'a>(
+    astro_object: &'a str,
+    entries: &'a [JsonEntry],
+    out: &mut Vec<(&'a str, &'a JsonEntry)>,
+) {
+    for e in entries {
+        out.push((astro_object, e));
+        flatten(astro_object, &e.entries, out);
+    }
+}
+
+/// Counts of facts and cards
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct Stats {
+    pub facts_revealed: usize,
+    pub facts_total: usize,
+    /// Cards, which are not hidden
+    pub cards_found: usize,
+    pub cards_total: usize,
+    pub rumor_only: usize,
+    pub more_to_explore: usize,
+}
+
+/// Rumors, which are drawn as one arrow between two cards
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JoinedRumors {
+    /// Sorted ids of entries
+    pub entries: [String; 2],
+    pub rumors: Vec<String>,
+}
+
+/// Progress of save, overall and grouped by astro objects and curiosities.
+/// Cards and rumors are in the same form, as site uses them
+#[derive(Debug, Default, Serialize)]
+pub struct Progress {
+    pub overall: Stats,
+    pub astro_objects: BTreeMap<String, Stats>,
+    pub curiosities: BTreeMap<String, Stats>,
+    /// Ids of cards, which have only rumor facts revealed
+    pub rumor_only_cards: Vec<String>,
+    /// Ids of cards with "There's more to explore"
+    pub more_to_explore_cards: Vec<String>,
+    /// Map of `<entry id>,<entry id>` to rumors between these entries
+    pub joined_rumors: BTreeMap<String, JoinedRumors>,
+    /// Map of rumor only card id to id of its alternative name
+    pub alt_names: BTreeMap<String, String>,
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let percent = if self.facts_total == 0 {
+            0.0
+        } else {
+            self.facts_revealed as f32 * 100.0 / self.facts_total as f32
+        };
+        write!(
+            f,
+            "facts {}/{} ({percent:.1}%), cards {}/{}, rumor only {}, more to explore {}",
+            self.facts_revealed,
+            self.facts_total,
+            self.cards_found,
+            self.cards_total,
+            self.rumor_only,
+            self.more_to_explore
+        )
+    }
+}
+
+impl Display for Progress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "overall: {}", self.overall)?;
+        writeln!(f, "\nastro objects:")?;
+        for (id, stats) in &self.astro_objects {
+            writeln!(f, "  {id}: {stats}")?;
+        }
+        writeln!(f, "\ncuriosities:")?;
+        for (id, stats) in &self.curiosities {
+            writeln!(f, "  {id}: {stats}")?;
+        }
+        if !self.rumor_only_cards.is_empty() {
+            writeln!(f, "\nrumor only: {}", self.rumor_only_cards.join(", "))?;
+        }
+        if !self.more_to_explore_cards.is_empty() {
+            writeln!(
+                f,
+                "\nmore to explore: {}",
+                self.more_to_explore_cards.join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ow_save::FactSave;
+
+    use super::*;
+
+    fn save(revealed: &[&str]) -> Save {
+        let mut save = Save::default();
+        for (i, id) in revealed.iter().enumerate() {
+            save.ship_log_fact_saves.insert(
+                id.to_string(),
+                FactSave {
+                    id: id.to_string(),
+                    reveal_order: i as i32,
+                    ..Default::default()
+                },
+            );
+        }
+        save
+    }
+
+    #[test]
+    fn test_progress() {
+        let entries: Vec<AstroObject<JsonEntry>> = serde_json::from_str(
+            r#"[{"id": "TIMBER_HEARTH", "entries": [
+                {"id": "TH_VILLAGE", "curiosity": "TIME_LOOP", "facts": {
+                    "explore": [{"id": "TH_VILLAGE_X1"}, {"id": "TH_VILLAGE_X2", "ignore_more_to_explore": true}]
+                }},
+                {"id": "TH_OBSERVATORY", "facts": {
+                    "explore": [{"id": "TH_OBSERVATORY_X1"}],
+                    "rumor": [{"id": "TH_OBSERVATORY_R1", "source_id": "TH_VILLAGE"}]
+                }, "entries": [
+                    {"id": "TH_MUSEUM", "ignore_more_to_explore_condition": "MUSEUM_DONE", "facts": {
+                        "explore": [{"id": "TH_MUSEUM_X1"}, {"id": "TH_MUSEUM_X2"}]
+                    }}
+                ]}
+            ]}]"#,
+        )
+        .unwrap();
+
+        let save = save(&["TH_VILLAGE_X1", "TH_OBSERVATORY_R1", "TH_MUSEUM_X1"]);
+        let log = ShipLog::new(&entries, &save);
+        let progress = log.progress();
+        assert_eq!(
+            progress.overall,
+            Stats {
+                facts_revealed: 3,
+                facts_total: 6,
+                cards_found: 3,
+                cards_total: 3,
+                rumor_only: 1,
+                more_to_explore: 1,
+            }
+        );
+        assert_eq!(progress.rumor_only_cards, ["TH_OBSERVATORY"]);
+        // village: hidden fact is ignored and its rumor is revealed
+        assert_eq!(progress.more_to_explore_cards, ["TH_MUSEUM"]);
+        assert_eq!(progress.curiosities["TIME_LOOP"].facts_total, 2);
+        assert_eq!(progress.curiosities[OTHER_CURIOSITY].cards_total, 2);
+
+        // rumor leads to explored card now
+        let mut save = save.clone();
+        save.persistent_conditions
+            .insert("MUSEUM_DONE".to_string(), true);
+        save.ship_log_fact_saves.insert(
+            "TH_OBSERVATORY_X1".to_string(),
+            FactSave {
+                reveal_order: 3,
+                ..Default::default()
+            },
+        );
+        let progress = ShipLog::new(&entries, &save).progress();
+        assert_eq!(progress.overall.more_to_explore, 0);
+        assert!(progress.rumor_only_cards.is_empty());
+    }
+
+    #[test]
+    fn test_parents_and_rumors() {
+        let entries: Vec<AstroObject<JsonEntry>> = serde_json::from_str(
+            r#"[{"id": "INVISIBLE_PLANET", "entries": [
+                {"id": "IP_ZONE_1", "facts": {"explore": [{"id": "IP_ZONE_1_X1"}]}, "entries": [
+                    {"id": "IP_ZONE_1_STORY", "facts": {"explore": [{"id": "IP_ZONE_1_STORY_X1"}, {"id": "IP_ZONE_1_STORY_X2"}]}}
+                ]},
+                {"id": "IP_ZONE_3_LAB", "facts": {"explore": [{"id": "IP_ZONE_3_LAB_X1"}]}, "entries": [
+                    {"id": "IP_MAP_PROJECTION_1", "facts": {"explore": [{"id": "IP_MAP_PROJECTION_1_X1"}]}},
+                    {"id": "IP_MAP_PROJECTION_2", "parent_ignore_not_revealed": true, "facts": {"explore": [{"id": "IP_MAP_PROJECTION_2_X1"}]}}
+                ]},
+                {"id": "IP_PRISON", "facts": {"explore": [{"id": "IP_PRISON_X1"}], "rumor": [
+                    {"id": "IP_PRISON_R1", "source_id": "IP_ZONE_1", "name_id": "SUBMERGED_STRUCTURE", "name_priority": 2},
+                    {"id": "IP_PRISON_R2", "source_id": "IP_ZONE_1", "name_id": "HOLLOW_STRUCTURE", "name_priority": 1},
+                    {"id": "IP_PRISON_R3", "source_id": "IP_ZONE_3_LAB", "ignore_more_to_explore": true}
+                ]}}
+            ]}]"#,
+        )
+        .unwrap();
+
+        let first = save(&[
+            "IP_ZONE_1_X1",
+            "IP_ZONE_1_STORY_X1",
+            "IP_MAP_PROJECTION_1_X1",
+            "IP_MAP_PROJECTION_2_X1",
+            "IP_PRISON_R2",
+            "IP_PRISON_R1",
+        ]);
+        let log = ShipLog::new(&entries, &first);
+        let entry = |i: usize| &entries[0].entries[i];
+
+        // parent is hidden
+        let projection = &entry(1).entries[0];
+        assert_eq!(log.state(projection), CardState::Explored);
+        assert_eq!(log.card_state(projection), CardState::Hidden);
+        assert_eq!(log.card_state(&entry(1).entries[1]), CardState::Explored);
+
+        let progress = log.progress();
+        assert_eq!(progress.overall.cards_found, 4);
+        assert_eq!(progress.rumor_only_cards, ["IP_PRISON"]);
+        // story has hidden fact, zone 1 has it from child
+        assert_eq!(
+            progress.more_to_explore_cards,
+            ["IP_ZONE_1", "IP_ZONE_1_STORY"]
+        );
+        assert_eq!(progress.alt_names["IP_PRISON"], "SUBMERGED_STRUCTURE");
+        assert_eq!(
+            progress.joined_rumors["IP_PRISON,IP_ZONE_1"],
+            JoinedRumors {
+                entries: ["IP_PRISON".to_string(), "IP_ZONE_1".to_string()],
+                rumors: vec!["IP_PRISON_R1".to_string(), "IP_PRISON_R2".to_string()],
+            }
+        );
+
+        // rumor from lab is ignored, so lab has nothing to explore
+        let second = save(&["IP_ZONE_3_LAB_X1", "IP_ZONE_1_X1", "IP_ZONE_1_STORY_X1"]);
+        let log = ShipLog::new(&entries, &second);
+        assert!(!log.has_more_to_explore(entry(1)));
+        assert!(log.has_more_to_explore(entry(0)));
+    }
+
+    #[test]
+    fn test_ignored_parent() {
+        let entries: Vec<AstroObject<JsonEntry>> = serde_json::from_str(
+            r#"[{"id": "DARK_BRAMBLE", "entries": [
+                {"id": "DB_FROZEN_JELLYFISH", "ignore_more_to_explore": true, "facts": {"explore": [{"id": "DB_FROZEN_JELLYFISH_X1"}]}, "entries": [
+                    {"id": "DB_NEST", "facts": {"explore": [{"id": "DB_NEST_X1"}, {"id": "DB_NEST_X2"}]}}
+                ]},
+                {"id": "DB_VESSEL", "ignore_more_to_explore_condition": "VESSEL_DONE", "facts": {"explore": [{"id": "DB_VESSEL_X1"}]}, "entries": [
+                    {"id": "DB_ESCAPE_POD", "facts": {"explore": [{"id": "DB_ESCAPE_POD_X1"}, {"id": "DB_ESCAPE_POD_X2"}]}}
+                ]}
+            ]}]"#,
+        )
+        .unwrap();
+        let entry = |i: usize| &entries[0].entries[i];
+
+        let mut save = save(&[
+            "DB_FROZEN_JELLYFISH_X1",
+            "DB_NEST_X1",
+            "DB_VESSEL_X1",
+            "DB_ESCAPE_POD_X1",
+        ]);
+        let log = ShipLog::new(&entries, &save);
+        // child with hidden fact doesn't mark ignored parent
+        assert!(log.has_more_to_explore(&entry(0).entries[0]));
+        assert!(!log.has_more_to_explore(entry(0)));
+        // condition isn't set yet
+        assert!(log.has_more_to_explore(entry(1)));
+
+        save.persistent_conditions
+            .insert("VESSEL_DONE".to_string(), true);
+        let log = ShipLog::new(&entries, &save);
+        assert!(log.has_more_to_explore(&entry(1).entries[0]));
+        assert!(!log.has_more_to_explore(entry(1)));
+    }
+}
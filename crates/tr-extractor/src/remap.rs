@@ -0,0 +1,411 @@
+//! Mapping of translation tables to ids of entries and facts
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use tracing::{debug, error, warn};
+
+use crate::{
+    MORE_TO_EXPLORE_EXTRACT_KEY, MORE_TO_EXPLORE_TR_KEY,
+    assets::TrObject,
+    detect::{DetectError, detect_lang},
+    info::Lang,
+    models::{
+        entries::{AstroObject, JsonEntry},
+        translations::Translation,
+    },
+    profile::Profile,
+    tree::{collect_astro_names, collect_astro_texts},
+};
+
+/// Translation of entries and facts, mapped by their ids. BTreeMap is used for
+/// sorting keys
+pub type IdTranslation = BTreeMap<String, String>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum RemapError {
+    #[error("missing text for {id} in {} table from {file}", lang.file_name())]
+    MissingText {
+        id: String,
+        lang: Lang,
+        file: String,
+    },
+
+    #[error("missing name for {id} in {} table from {file}", lang.file_name())]
+    MissingName {
+        id: String,
+        lang: Lang,
+        file: String,
+    },
+
+    #[error("missing rumor alt name {id} in {} table from {file}", lang.file_name())]
+    MissingRumorAltName {
+        id: String,
+        lang: Lang,
+        file: String,
+    },
+
+    #[error("unknown language of table {index} from {file}: {source}")]
+    UnknownLanguage {
+        index: usize,
+        file: String,
+        source: DetectError,
+    },
+
+    #[error("table {index} from {file} detected as {}, which is already found", lang.file_name())]
+    DuplicateLanguage {
+        index: usize,
+        lang: Lang,
+        file: String,
+    },
+
+    #[error("missing translation table for {}", .0.file_name())]
+    MissingLanguage(Lang),
+}
+
+/// Translations of entries and UI texts for all languages
+#[derive(Debug)]
+pub struct Remapped {
+    pub translations: HashMap<Lang, IdTranslation>,
+    /// Full UI tables, mapped by their numeric keys
+    pub ui: HashMap<Lang, IdTranslation>,
+}
+
+/// All errors, found while remapping translations
+#[derive(Debug)]
+pub struct RemapErrors(pub Vec<RemapError>);
+
+impl std::fmt::Display for RemapErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} errors while remapping translations:", self.0.len())?;
+        for e in &self.0 {
+            write!(f, "\n  {e}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for RemapErrors {}
+
+/// Build translations for all languages, mapped by ids of entries and facts.
+/// `rumor_alt_names` is map of `"Rumor alt name" -> "RUMOR_ALT_NAME"`
+pub fn remap_translations(
+    astro_objects: &[AstroObject<JsonEntry>],
+    rumor_alt_names: &HashMap<String, String>,
+    tr_objects: Vec<TrObject>,
+    profile: &Profile,
+) -> Result<Remapped, RemapErrors> {
+    // names and ids of astro objects for searching in translations
+    let mut astro_names_keys = HashMap::<String, Vec<String>>::with_capacity(100);
+    for a in astro_objects {
+        // different ids can have same name
+        for (name, id) in collect_astro_names(&a.entries) {
+            astro_names_keys
+                .entry(name)
+                .and_modify(|ids| ids.push(id.clone()))
+                .or_insert_with(|| vec![id]);
+        }
+    }
+    debug!("count of astro names: {}", astro_names_keys.len());
+
+    // keys for texts
+    let mut astro_facts = HashMap::<String, Vec<String>>::with_capacity(400);
+    for a in astro_objects {
+        // different ids can have same text
+        for (text, id) in collect_astro_texts(&a.entries) {
+            astro_facts
+                .entry(text)
+                .and_modify(|ids| ids.push(id.clone()))
+                .or_insert_with(|| vec![id]);
+        }
+    }
+    debug!("count of astro texts: {}", astro_facts.len());
+
+    // remap translations
+    let mut translations = HashMap::new();
+    let mut ui_translations = HashMap::new();
+    let names: HashSet<&str> = astro_names_keys.keys().map(String::as_str).collect();
+    let texts: HashSet<&str> = astro_facts
+        .keys()
+        .chain(rumor_alt_names.keys())
+        .map(String::as_str)
+        .chain([MORE_TO_EXPLORE_EXTRACT_KEY])
+        .collect();
+    let (cleaned, mut errors) = clean_translations(tr_objects, &names, &texts, profile);
+    for (lang, (file, tr, ui)) in cleaned {
+        ui_translations.insert(lang, ui);
+
+        // BTreeMap is used for sorting keys
+        let mut translation = BTreeMap::new();
+        for (text, ids) in &astro_facts {
+            for id in ids {
+                match tr.get(text) {
+                    Some(t) => {
+                        translation.insert(id.to_owned(), t.to_owned());
+                    }
+                    None => errors.push(RemapError::MissingText {
+                        id: id.to_owned(),
+                        lang,
+                        file: file.clone(),
+                    }),
+                }
+            }
+        }
+        for (name, ids) in &astro_names_keys {
+            for id in ids {
+                match tr.get(name) {
+                    Some(t) => {
+                        translation.insert(id.to_owned(), t.to_owned());
+                    }
+                    None => errors.push(RemapError::MissingName {
+                        id: id.to_owned(),
+                        lang,
+                        file: file.clone(),
+                    }),
+                }
+            }
+        }
+        for (text, generated_id) in rumor_alt_names.iter() {
+            let Some(translated) = tr.get(text) else {
+                errors.push(RemapError::MissingRumorAltName {
+                    id: generated_id.to_owned(),
+                    lang,
+                    file: file.clone(),
+                });
+                continue;
+            };
+            if translation
+                .get(generated_id)
+                .is_some_and(|t| t != translated)
+            {
+                error!("rumor alt name {generated_id} clashes with regular translation");
+            }
+            translation.insert(generated_id.to_owned(), translated.to_owned());
+        }
+        match tr.get(MORE_TO_EXPLORE_EXTRACT_KEY) {
+            Some(t) => {
+                translation.insert(MORE_TO_EXPLORE_TR_KEY.to_owned(), t.to_owned());
+            }
+            None => errors.push(RemapError::MissingText {
+                id: MORE_TO_EXPLORE_TR_KEY.to_owned(),
+                lang,
+                file: file.clone(),
+            }),
+        }
+        debug!(
+            "count of keys in {} translation: {}",
+            lang.file_name(),
+            translation.len()
+        );
+
+        translations.insert(lang, translation);
+    }
+
+    if !errors.is_empty() {
+        return Err(RemapErrors(errors));
+    }
+    Ok(Remapped {
+        translations,
+        ui: ui_translations,
+    })
+}
+
+/// Clean translation keys from prefixes and map translations to keys for all
+/// languages
+///
+/// Keys of names and rumor alt names are texts as is, and keys of facts start
+/// with name of entry, e.g "VillageThe one and only Hearthian village, ...".
+/// Each key is matched against exact `names` and `texts`, see [`clean_key`].
+/// Keys, which don't match, are reported and dropped
+///
+/// Values are kept as is, markup is converted when writing them, see
+/// [`crate::richtext`]
+///
+/// Returns map of language to name of file with table, cleaned table and UI
+/// table
+#[allow(clippy::type_complexity)]
+fn clean_translations(
+    tr_objects: Vec<TrObject>,
+    names: &HashSet<&str>,
+    texts: &HashSet<&str>,
+    profile: &Profile,
+) -> (
+    HashMap<Lang, (String, HashMap<String, String>, IdTranslation)>,
+    Vec<RemapError>,
+) {
+    let mut errors = vec![];
+    // keys are the same in all tables, so report them once
+    let mut unattributed = BTreeSet::new();
+
+    let mut lang_order = Vec::with_capacity(tr_objects.len());
+    let mut translations = HashMap::new();
+    for (index, TrObject { file, entries, ui }) in tr_objects.into_iter().enumerate() {
+        let lang = match detect_lang(&entries) {
+            Ok(lang) => lang,
+            Err(source) => {
+                errors.push(RemapError::UnknownLanguage {
+                    index,
+                    file,
+                    source,
+                });
+                continue;
+            }
+        };
+        debug!("translation table {index} is {}", lang.file_name());
+        if lang_order.contains(&lang) {
+            errors.push(RemapError::DuplicateLanguage { index, lang, file });
+            continue;
+        }
+        lang_order.push(lang);
+
+        let mut translation = HashMap::new();
+        for Translation { key, value } in entries {
+            match clean_key(&key, names, texts) {
+                Some(cleaned) => {
+                    translation.insert(cleaned.to_string(), value);
+                }
+                None => {
+                    unattributed.insert(key);
+                }
+            }
+        }
+        let mut ui_table = BTreeMap::new();
+        for Translation { key, value } in ui {
+            if ui_table.contains_key(&key) {
+                debug!("duplicate ui key {key} in {} table", lang.file_name());
+                continue;
+            }
+            ui_table.insert(key, value);
+        }
+        translations.insert(lang, (file, translation, ui_table));
+    }
+
+    for key in unattributed {
+        warn!("translation key {key:?} doesn't match any entry name or text");
+    }
+    for lang in Lang::ALL {
+        if !lang_order.contains(lang) {
+            errors.push(RemapError::MissingLanguage(*lang));
+        }
+    }
+    if lang_order != profile.lang_order {
+        warn!(
+            "order of translation tables differs from profile for game version {}",
+            profile.game_version
+        );
+    }
+
+    (translations, errors)
+}
+
+/// Strip prefix from translation key. Key, equal to name or text, is kept as
+/// is. Otherwise the longest name, after which the rest of key is a text, is
+/// stripped. Returns `None` if key doesn't match
+fn clean_key<'k>(key: &'k str, names: &HashSet<&str>, texts: &HashSet<&str>) -> Option<&'k str> {
+    if names.contains(key) || texts.contains(key) {
+        return Some(key);
+    }
+    (1..key.len())
+        .rev()
+        .filter(|&i| key.is_char_boundary(i))
+        .find(|&i| names.contains(&key[..i]) && texts.contains(&key[i..]))
+        .map(|i| &key[i..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::entries::{ExploreFact, JsonEntryFacts, RumorFact};
+
+    #[test]
+    fn test_clean_key() {
+        let names = HashSet::from(["Escape Pod 3", "Escape Pod 3 Survivors", "Ship", "Ship Log"]);
+        let texts = HashSet::from([" entry", "Log entry", "Survivors text", "Alt Name"]);
+
+        // names are kept, even if they start with other name
+        assert_eq!(
+            clean_key("Escape Pod 3 Survivors", &names, &texts),
+            Some("Escape Pod 3 Survivors")
+        );
+        assert_eq!(
+            clean_key("Escape Pod 3Survivors text", &names, &texts),
+            Some("Survivors text")
+        );
+        // the longest valid prefix wins
+        assert_eq!(clean_key("Ship Log entry", &names, &texts), Some(" entry"));
+        assert_eq!(
+            clean_key("ShipLog entry", &names, &texts),
+            Some("Log entry")
+        );
+        assert_eq!(clean_key("Alt Name", &names, &texts), Some("Alt Name"));
+        assert_eq!(clean_key("ShipUnknown", &names, &texts), None);
+        assert_eq!(clean_key("Unknown", &names, &texts), None);
+    }
+
+    #[test]
+    fn test_remap_errors_collected() {
+        let astro_objects = vec![
+            AstroObject::builder()
+                .id("TIMBER_HEARTH".to_string())
+                .entries(vec![JsonEntry {
+                    id: "TH_VILLAGE".to_string(),
+                    name: "Village".to_string(),
+                    facts: JsonEntryFacts {
+                        explore: vec![
+                            ExploreFact::builder()
+                                .id("TH_VILLAGE_X1".to_string())
+                                .text("Hello there".to_string())
+                                .build(),
+                            ExploreFact::builder()
+                                .id("TH_VILLAGE_X2".to_string())
+                                .text("Not translated".to_string())
+                                .build(),
+                        ],
+                        rumor: vec![
+                            RumorFact::builder()
+                                .id("TH_VILLAGE_R1".to_string())
+                                .name("Alt Name".to_string())
+                                .text("Hello there".to_string())
+                                .build(),
+                        ],
+                    },
+                    ..Default::default()
+                }])
+                .build(),
+        ];
+        let rumor_alt_names = HashMap::from([("Alt Name".to_string(), "ALT_NAME".to_string())]);
+        let table = |key: &str, value: &str| Translation {
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+        let tr_objects = vec![TrObject {
+            file: "resources.assets".to_string(),
+            entries: vec![table("VillageHello there", "Hello there")],
+            ui: vec![],
+        }];
+        let profile = crate::profile::profiles().unwrap().remove(0);
+
+        let errors = remap_translations(&astro_objects, &rumor_alt_names, tr_objects, &profile)
+            .unwrap_err()
+            .0;
+        let mut missing: Vec<_> = errors
+            .iter()
+            .filter_map(|e| match e {
+                RemapError::MissingText { id, lang, file }
+                | RemapError::MissingName { id, lang, file }
+                | RemapError::MissingRumorAltName { id, lang, file } => {
+                    assert_eq!(*lang, Lang::English);
+                    assert_eq!(file, "resources.assets");
+                    Some(id.as_str())
+                }
+                RemapError::MissingLanguage(_) => None,
+                e => panic!("unexpected error: {e}"),
+            })
+            .collect();
+        missing.sort();
+        assert_eq!(
+            missing,
+            ["ALT_NAME", "MORE_TO_EXPLORE", "TH_VILLAGE", "TH_VILLAGE_X2"]
+        );
+        assert_eq!(errors.len(), 4 + Lang::ALL.len() - 1);
+    }
+}
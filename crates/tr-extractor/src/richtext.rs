@@ -0,0 +1,279 @@
+//! Unity rich text in translated values
+//!
+//! Values can contain Unity markup, like `<color=orange>`, `<b>` or
+//! `<size=20>`, and escapes from XML tables. They are converted either to
+//! plain text, or to small subset of HTML, which is safe to insert into page.
+//!
+//! Escapes:
+//! - `\n` and backslash before line break are line breaks
+//! - `\\N`, found in a few values of some languages, is kept as is
+
+use std::collections::HashMap;
+
+use tracing::warn;
+
+use crate::{info::Lang, remap::IdTranslation};
+
+/// Tags, supported by Unity's UI text. Names are case-insensitive
+const KNOWN_TAGS: &[&str] = &[
+    "b", "i", "u", "s", "color", "size", "material", "quad", "sub", "sup",
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum RichTextMode {
+    /// Remove all markup
+    #[default]
+    Plain,
+    /// Convert markup to `<b>`, `<i>`, `<u>`, `<s>`, `<sub>`, `<sup>`, `<br>`
+    /// and `<span>` with color. Other text is escaped
+    Html,
+}
+
+/// Result of conversion
+#[derive(Debug, PartialEq)]
+pub struct Converted {
+    pub text: String,
+    /// Tag-like sequences, which are not Unity markup. They are kept as text
+    pub unknown_tags: Vec<String>,
+}
+
+/// Convert all values of translations, reporting unknown tags
+pub fn render_translations(
+    translations: &HashMap<Lang, IdTranslation>,
+    mode: RichTextMode,
+) -> HashMap<Lang, IdTranslation> {
+    translations
+        .iter()
+        .map(|(lang, tr)| {
+            let tr = tr
+                .iter()
+                .map(|(id, text)| {
+                    let converted = convert(text, mode);
+                    for tag in &converted.unknown_tags {
+                        warn!("unknown tag {tag} in {id} ({})", lang.file_name());
+                    }
+                    (id.clone(), converted.text)
+                })
+                .collect();
+            (*lang, tr)
+        })
+        .collect()
+}
+
+/// Replace escapes from translation tables
+// TODO: find out how game shows `\\N` before converting it
+pub fn unescape(text: &str) -> String {
+    text.replace("\\n", "\n").replace("\\\n", "\n")
+}
+
+/// Convert value with markup and escapes
+pub fn convert(text: &str, mode: RichTextMode) -> Converted {
+    let text = unescape(text);
+    let mut out = Converted {
+        text: String::with_capacity(text.len()),
+        unknown_tags: vec![],
+    };
+    // open tags in html mode, to close them in right order
+    let mut open: Vec<&str> = vec![];
+
+    let mut rest = text.as_str();
+    while let Some(start) = rest.find('<') {
+        push_text(&mut out.text, &rest[..start], mode);
+        rest = &rest[start..];
+        let Some(tag) = parse_tag(rest) else {
+            push_text(&mut out.text, "<", mode);
+            rest = &rest[1..];
+            continue;
+        };
+        let raw = &rest[..tag.len];
+        rest = &rest[tag.len..];
+        let Some(name) = KNOWN_TAGS
+            .iter()
+            .copied()
+            .find(|t| t.eq_ignore_ascii_case(tag.name))
+        else {
+            out.unknown_tags.push(raw.to_string());
+            push_text(&mut out.text, raw, mode);
+            continue;
+        };
+        if mode == RichTextMode::Plain {
+            continue;
+        }
+
+        let Some(html) = html_tag(name) else {
+            continue;
+        };
+        if tag.closing {
+            // close tags up to matching one, ignore stray closing tags
+            if let Some(pos) = open.iter().rposition(|t| *t == html) {
+                for t in open.drain(pos..).rev() {
+                    out.text.push_str(&format!("</{t}>"));
+                }
+            }
+            continue;
+        }
+        match (name, tag.value) {
+            ("color", Some(color)) if is_safe_color(color) => {
+                out.text
+                    .push_str(&format!("<span style=\"color: {color}\">"));
+            }
+            // invalid color is ignored, but closing tag still should match
+            ("color", _) => out.text.push_str("<span>"),
+            _ => out.text.push_str(&format!("<{html}>")),
+        }
+        open.push(html);
+    }
+    push_text(&mut out.text, rest, mode);
+    for t in open.into_iter().rev() {
+        out.text.push_str(&format!("</{t}>"));
+    }
+    out
+}
+
+struct Tag<'a> {
+    name: &'a str,
+    value: Option<&'a str>,
+    closing: bool,
+    /// Length of tag in text
+    len: usize,
+}
+
+/// Parse `<name>`, `<name=value>` or `</name>` at start of text
+fn parse_tag(text: &str) -> Option<Tag<'_>> {
+    let end = text.find('>')?;
+    let inner = &text[1..end];
+    let (closing, inner) = match inner.strip_prefix('/') {
+        Some(inner) => (true, inner),
+        None => (false, inner),
+    };
+    let (name, value) = match inner.split_once('=') {
+        Some((name, value)) => (name, Some(value.trim_matches('"'))),
+        None => (inner, None),
+    };
+    let valid_name = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    if !valid_name || (closing && value.is_some()) {
+        return None;
+    }
+    Some(Tag {
+        name,
+        value,
+        closing,
+        len: end + 1,
+    })
+}
+
+/// HTML tag for Unity tag. `None` for tags, which are dropped
+fn html_tag(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "b" => "b",
+        "i" => "i",
+        "u" => "u",
+        "s" => "s",
+        "sub" => "sub",
+        "sup" => "sup",
+        "color" => "span",
+        _ => return None,
+    })
+}
+
+/// `#rgb`, `#rrggbb`, `#rrggbbaa` or color name
+fn is_safe_color(color: &str) -> bool {
+    match color.strip_prefix('#') {
+        Some(hex) => matches!(hex.len(), 3 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit()),
+        None => !color.is_empty() && color.chars().all(|c| c.is_ascii_alphabetic()),
+    }
+}
+
+fn push_text(out: &mut String, text: &str, mode: RichTextMode) {
+    match mode {
+        RichTextMode::Plain => out.push_str(text),
+        RichTextMode::Html => {
+            for c in text.chars() {
+                match c {
+                    '&' => out.push_str("&amp;"),
+                    '<' => out.push_str("&lt;"),
+                    '>' => out.push_str("&gt;"),
+                    '"' => out.push_str("&quot;"),
+                    '\n' => out.push_str("<br>"),
+                    c => out.push(c),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(text: &str) -> String {
+        convert(text, RichTextMode::Plain).text
+    }
+
+    fn html(text: &str) -> String {
+        convert(text, RichTextMode::Html).text
+    }
+
+    #[test]
+    fn test_escapes() {
+        assert_eq!(plain("a\\nb"), "a\nb");
+        assert_eq!(plain("a\\\nb"), "a\nb");
+        // not known how game shows it
+        assert_eq!(plain("a\\\\Nb"), "a\\\\Nb");
+        // single backslash before N is kept
+        assert_eq!(plain("a\\Nb"), "a\\Nb");
+        assert_eq!(html("a\\nb"), "a<br>b");
+    }
+
+    #[test]
+    fn test_plain() {
+        assert_eq!(
+            plain("<color=orange>There's more to explore here.</color>"),
+            "There's more to explore here."
+        );
+        assert_eq!(plain("<b>bold</b> <size=20>big</size>"), "bold big");
+        assert_eq!(plain("1 < 2 > 0"), "1 < 2 > 0");
+    }
+
+    #[test]
+    fn test_html() {
+        assert_eq!(
+            html("<color=orange>more</color> & <b><i>x</b>"),
+            "<span style=\"color: orange\">more</span> &amp; <b><i>x</i></b>"
+        );
+        assert_eq!(
+            html("<color=#ff0000ff>red</color>"),
+            "<span style=\"color: #ff0000ff\">red</span>"
+        );
+        // values are not copied to html when not safe
+        assert_eq!(
+            html("<color=\"red;background:url(x)\">a</color>"),
+            "<span>a</span>"
+        );
+        assert_eq!(html("<size=20>big</size></i>"), "big");
+        assert_eq!(html("<b>unclosed"), "<b>unclosed</b>");
+        assert_eq!(html("1 < 2"), "1 &lt; 2");
+    }
+
+    #[test]
+    fn test_tag_case() {
+        assert_eq!(
+            html("<B>bold</b> <Color=orange>more</COLOR>"),
+            "<b>bold</b> <span style=\"color: orange\">more</span>"
+        );
+        assert_eq!(plain("<I>x</I>"), "x");
+        assert!(
+            convert("<B>a</B>", RichTextMode::Html)
+                .unknown_tags
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_unknown_tags() {
+        let converted = convert("<link=x>a</link> <b>b</b>", RichTextMode::Html);
+        assert_eq!(converted.unknown_tags, ["<link=x>", "</link>"]);
+        assert_eq!(converted.text, "&lt;link=x&gt;a&lt;/link&gt; <b>b</b>");
+    }
+}
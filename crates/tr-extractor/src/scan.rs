@@ -0,0 +1,122 @@
+//! Single-pass search for XML objects in asset files
+
+use std::ops::Range;
+
+use aho_corasick::AhoCorasick;
+use tracing::warn;
+
+/// XML objects to search for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectKind {
+    AstroObject,
+    ShiplogTable,
+    UiTable,
+}
+
+impl ObjectKind {
+    const ALL: [Self; 3] = [Self::AstroObject, Self::ShiplogTable, Self::UiTable];
+
+    fn start_marker(self) -> &'static [u8] {
+        match self {
+            Self::AstroObject => b"<AstroObjectEntry>",
+            Self::ShiplogTable => b"<table_shipLog>",
+            Self::UiTable => b"<table_ui>",
+        }
+    }
+
+    fn end_marker(self) -> &'static [u8] {
+        match self {
+            Self::AstroObject => b"</AstroObjectEntry>",
+            Self::ShiplogTable => b"</table_shipLog>",
+            Self::UiTable => b"</table_ui>",
+        }
+    }
+}
+
+/// Byte ranges of found objects, in order of appearance
+#[derive(Debug, Default)]
+pub struct ObjectIndex {
+    pub astro_objects: Vec<Range<usize>>,
+    pub shiplog_tables: Vec<Range<usize>>,
+    pub ui_tables: Vec<Range<usize>>,
+}
+
+impl ObjectIndex {
+    fn ranges_mut(&mut self, kind: ObjectKind) -> &mut Vec<Range<usize>> {
+        match kind {
+            ObjectKind::AstroObject => &mut self.astro_objects,
+            ObjectKind::ShiplogTable => &mut self.shiplog_tables,
+            ObjectKind::UiTable => &mut self.ui_tables,
+        }
+    }
+}
+
+pub struct Scanner {
+    searcher: AhoCorasick,
+}
+
+impl Scanner {
+    pub fn new() -> Self {
+        // patterns are ordered as [start, end] for each kind
+        let patterns = ObjectKind::ALL
+            .iter()
+            .flat_map(|k| [k.start_marker(), k.end_marker()]);
+        Self {
+            searcher: AhoCorasick::new(patterns).expect("markers should be valid patterns"),
+        }
+    }
+
+    /// Find all objects in data with one pass
+    pub fn scan(&self, data: &[u8]) -> ObjectIndex {
+        let mut index = ObjectIndex::default();
+        let mut open: [Option<usize>; ObjectKind::ALL.len()] = Default::default();
+        for m in self.searcher.find_iter(data) {
+            let pattern = m.pattern().as_usize();
+            let kind = ObjectKind::ALL[pattern / 2];
+            let is_start = pattern % 2 == 0;
+            let open = &mut open[pattern / 2];
+
+            if is_start {
+                if let Some(start) = open.replace(m.start()) {
+                    warn!("unclosed {kind:?} at offset {start}");
+                }
+            } else if let Some(start) = open.take() {
+                index.ranges_mut(kind).push(start..m.end());
+            } else {
+                warn!("unexpected end of {kind:?} at offset {}", m.start());
+            }
+        }
+        index
+    }
+}
+
+impl Default for Scanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan() {
+        let data = b"\x00\x01<table_shipLog><a/></table_shipLog>\xff<table_ui></table_ui>\
+            </AstroObjectEntry><AstroObjectEntry><b/></AstroObjectEntry>\x00\
+            <table_shipLog>";
+        let index = Scanner::new().scan(data);
+        let slices = |ranges: Vec<Range<usize>>| -> Vec<&[u8]> {
+            ranges.into_iter().map(|r| &data[r]).collect()
+        };
+        assert_eq!(
+            slices(index.astro_objects),
+            [b"<AstroObjectEntry><b/></AstroObjectEntry>"]
+        );
+        assert_eq!(
+            slices(index.shiplog_tables),
+            [b"<table_shipLog><a/></table_shipLog>"]
+        );
+        assert_eq!(slices(index.ui_tables), [b"<table_ui></table_ui>"]);
+    }
+}
@@ -0,0 +1,230 @@
+//! Card sprites: find textures of ship log entries and convert them to JPEG
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use tracing::{debug, info};
+
+use crate::{
+    GameData,
+    assets::{map_asset_files, parse_asset_files},
+    library::AssetRef,
+    output::OutputFile,
+    texture::{Image, decode},
+    tree::collect_entry_ids,
+    unity::{CLASS_SPRITE, CLASS_TEXTURE_2D, ImageData, ObjectInfo, SerializedFile},
+};
+
+/// Directory with sprites in output directory. Alternative sprites are
+/// placed in `alt` subdirectory
+pub const SPRITES_DIR: &str = "sprites";
+
+const JPEG_QUALITY: u8 = 90;
+
+#[derive(Debug, Default)]
+pub struct SpriteReport {
+    /// JPEG images, named by entry id
+    pub files: Vec<OutputFile>,
+
+    /// Entries without sprite in ship log library
+    pub missing: Vec<String>,
+
+    /// Sprites which failed to extract, with entry id. Sprites packed into
+    /// atlas and textures in formats, which can't be decoded, are reported
+    /// here with "unsupported format"
+    pub failed: Vec<(String, anyhow::Error)>,
+}
+
+/// Extract sprites of all entries
+pub fn extract_sprites(dir: &Path, data: &GameData) -> Result<SpriteReport> {
+    let Some(library) = &data.library else {
+        bail!("ship log library is not loaded, can't find sprites");
+    };
+    let sprites: HashMap<_, _> = library
+        .entries
+        .iter()
+        .map(|e| (e.id.as_str(), (&e.sprite, &e.alt_sprite)))
+        .collect();
+
+    let mapped = map_asset_files(dir)?;
+    let files: HashMap<_, _> = parse_asset_files(&mapped).into_iter().collect();
+
+    let mut report = SpriteReport::default();
+    for id in data
+        .astro_objects
+        .iter()
+        .flat_map(|a| collect_entry_ids(&a.entries))
+    {
+        let Some((Some(sprite), alt_sprite)) = sprites.get(id.as_str()) else {
+            report.missing.push(id);
+            continue;
+        };
+        let path = Path::new(SPRITES_DIR).join(format!("{id}.jpg"));
+        let alt_path = Path::new(SPRITES_DIR).join("alt").join(format!("{id}.jpg"));
+        for (sprite, path) in [(Some(sprite), path), (alt_sprite.as_ref(), alt_path)] {
+            let Some(sprite) = sprite else {
+                continue;
+            };
+            match sprite_jpeg(dir, &files, sprite) {
+                Ok(data) => report.files.push(OutputFile { path, data }),
+                Err(e) => report.failed.push((id.clone(), e)),
+            }
+        }
+    }
+    info!("extracted {} sprites", report.files.len());
+    Ok(report)
+}
+
+fn sprite_jpeg(
+    dir: &Path,
+    files: &HashMap<&str, SerializedFile>,
+    sprite_ref: &AssetRef,
+) -> Result<Vec<u8>> {
+    let (file, object) = find_object(files, sprite_ref)?;
+    if object.class_id != CLASS_SPRITE {
+        bail!("{sprite_ref:?} is not a sprite");
+    }
+    let sprite = file.sprite(object)?;
+    debug!("reading sprite {}", sprite.name);
+    if !sprite.atlas.is_null() {
+        bail!(
+            "unsupported format: sprite {} is packed into atlas",
+            sprite.name
+        );
+    }
+    let texture_ref = AssetRef::resolve(&sprite_ref.file, file, sprite.texture)
+        .with_context(|| format!("sprite {} has no texture", sprite.name))?;
+    let (file, object) = find_object(files, &texture_ref)?;
+    if object.class_id != CLASS_TEXTURE_2D {
+        bail!("{texture_ref:?} is not a texture");
+    }
+    let texture = file.texture_2d(object)?;
+
+    let streamed;
+    let pixels = match texture.image {
+        ImageData::Inline(data) => data,
+        ImageData::Stream { path, offset, size } => {
+            streamed = read_stream(dir, path, offset, size)?;
+            &streamed
+        }
+    };
+    let image = decode(texture.format, texture.width, texture.height, pixels)
+        .with_context(|| format!("decoding texture {}", texture.name))?;
+
+    // rect is measured from bottom of texture
+    let [x, y, width, height] = sprite.rect.map(|v| v.round().max(0.0) as u32);
+    let top = texture.height.saturating_sub(y + height);
+    let image = if (x, top, width, height) == (0, 0, texture.width, texture.height) {
+        image
+    } else {
+        image.crop(x, top, width, height)
+    };
+    encode_jpeg(&image)
+}
+
+fn find_object<'f, 'd>(
+    files: &'f HashMap<&str, SerializedFile<'d>>,
+    asset: &AssetRef,
+) -> Result<(&'f SerializedFile<'d>, &'f ObjectInfo)> {
+    let file = files
+        .get(asset.file.as_str())
+        .with_context(|| format!("file {} is not found", asset.file))?;
+    let object = file
+        .object(asset.path_id)
+        .with_context(|| format!("object {} is not found in {}", asset.path_id, asset.file))?;
+    Ok((file, object))
+}
+
+/// Read texture data from resource file near asset files
+fn read_stream(dir: &Path, path: &str, offset: u64, size: u32) -> Result<Vec<u8>> {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let path: PathBuf = dir.join(name);
+    let mut file = File::open(&path).with_context(|| format!("opening {}", path.display()))?;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut data = vec![0; size as usize];
+    file.read_exact(&mut data)
+        .with_context(|| format!("reading {}", path.display()))?;
+    Ok(data)
+}
+
+fn encode_jpeg(image: &Image) -> Result<Vec<u8>> {
+    let mut data = vec![];
+    jpeg_encoder::Encoder::new(&mut data, JPEG_QUALITY).encode(
+        &image.pixels,
+        image.width.try_into().context("image is too wide")?,
+        image.height.try_into().context("image is too high")?,
+        jpeg_encoder::ColorType::Rgba,
+    )?;
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        texture::format,
+        unity::tests::{build_file, sprite, texture_2d},
+    };
+
+    #[test]
+    fn test_sprite_jpeg() {
+        // 4x2 texture, sprite covers its right half
+        let pixels: Vec<u8> = (0..32).collect();
+        let file = build_file(&[
+            (
+                CLASS_TEXTURE_2D,
+                texture_2d("card", 4, 2, format::RGBA32, &pixels),
+            ),
+            (CLASS_SPRITE, sprite("card", 1, [2.0, 0.0, 2.0, 2.0])),
+        ]);
+        let parsed = SerializedFile::parse(&file).unwrap();
+
+        let texture = parsed.texture_2d(parsed.object(1).unwrap()).unwrap();
+        assert_eq!((texture.width, texture.height), (4, 2));
+        assert_eq!(texture.format, format::RGBA32);
+        assert!(matches!(texture.image, ImageData::Inline(p) if p == pixels));
+
+        let files = HashMap::from([("sharedassets0.assets", parsed)]);
+        let sprite = AssetRef {
+            file: "sharedassets0.assets".to_string(),
+            path_id: 2,
+        };
+        let jpeg = sprite_jpeg(Path::new("."), &files, &sprite).unwrap();
+        assert_eq!(jpeg[..2], [0xff, 0xd8]);
+
+        let texture = AssetRef {
+            file: "sharedassets0.assets".to_string(),
+            path_id: 1,
+        };
+        assert!(sprite_jpeg(Path::new("."), &files, &texture).is_err());
+    }
+
+    #[test]
+    fn test_unsupported_format() {
+        let file = build_file(&[
+            (
+                CLASS_TEXTURE_2D,
+                texture_2d("card", 4, 4, format::BC7, &[0; 16]),
+            ),
+            (CLASS_SPRITE, sprite("card", 1, [0.0, 0.0, 4.0, 4.0])),
+        ]);
+        let files = HashMap::from([(
+            "sharedassets0.assets",
+            SerializedFile::parse(&file).unwrap(),
+        )]);
+        let sprite = AssetRef {
+            file: "sharedassets0.assets".to_string(),
+            path_id: 2,
+        };
+        let err = sprite_jpeg(Path::new("."), &files, &sprite).unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "decoding texture card: unsupported format: texture format BC7 (25)"
+        );
+    }
+}
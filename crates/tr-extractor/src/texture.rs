@@ -0,0 +1,275 @@
+//! Decoding of Unity texture formats to RGBA pixels
+
+/// Values of Unity's `TextureFormat` enum
+pub mod format {
+    pub const ALPHA8: i32 = 1;
+    pub const RGB24: i32 = 3;
+    pub const RGBA32: i32 = 4;
+    pub const ARGB32: i32 = 5;
+    pub const DXT1: i32 = 10;
+    pub const DXT5: i32 = 12;
+    pub const BGRA32: i32 = 14;
+    pub const BC6H: i32 = 24;
+    pub const BC7: i32 = 25;
+    pub const DXT1_CRUNCHED: i32 = 28;
+    pub const DXT5_CRUNCHED: i32 = 29;
+
+    /// Name of format for messages, only formats which are known to appear in
+    /// games are named
+    pub fn name(format: i32) -> String {
+        let name = match format {
+            ALPHA8 => "Alpha8",
+            RGB24 => "RGB24",
+            RGBA32 => "RGBA32",
+            ARGB32 => "ARGB32",
+            DXT1 => "DXT1",
+            DXT5 => "DXT5",
+            BGRA32 => "BGRA32",
+            BC6H => "BC6H",
+            BC7 => "BC7",
+            DXT1_CRUNCHED => "DXT1Crunched",
+            DXT5_CRUNCHED => "DXT5Crunched",
+            _ => return format.to_string(),
+        };
+        format!("{name} ({format})")
+    }
+}
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum TextureError {
+    #[error("unsupported format: texture format {}", format::name(*.0))]
+    UnsupportedFormat(i32),
+
+    #[error("texture data is too short: expected {expected} bytes, got {actual}")]
+    TooShort { expected: usize, actual: usize },
+}
+
+/// Decoded image with RGBA pixels, rows from top to bottom
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Copy part of image. Rectangle is clamped to image bounds
+    pub fn crop(&self, x: u32, y: u32, width: u32, height: u32) -> Image {
+        let x = x.min(self.width);
+        let y = y.min(self.height);
+        let width = width.min(self.width - x);
+        let height = height.min(self.height - y);
+        let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+        for row in y..y + height {
+            let start = ((row * self.width + x) * 4) as usize;
+            pixels.extend_from_slice(&self.pixels[start..start + (width * 4) as usize]);
+        }
+        Image {
+            width,
+            height,
+            pixels,
+        }
+    }
+}
+
+/// Decode largest mip level of texture. Unity stores rows from bottom to top,
+/// so image is flipped
+pub fn decode(format: i32, width: u32, height: u32, data: &[u8]) -> Result<Image, TextureError> {
+    let (w, h) = (width as usize, height as usize);
+    let expected = match format {
+        format::ALPHA8 => w * h,
+        format::RGB24 => w * h * 3,
+        format::RGBA32 | format::ARGB32 | format::BGRA32 => w * h * 4,
+        format::DXT1 => w.div_ceil(4) * h.div_ceil(4) * 8,
+        format::DXT5 => w.div_ceil(4) * h.div_ceil(4) * 16,
+        _ => return Err(TextureError::UnsupportedFormat(format)),
+    };
+    if data.len() < expected {
+        return Err(TextureError::TooShort {
+            expected,
+            actual: data.len(),
+        });
+    }
+    let data = &data[..expected];
+
+    let mut pixels = match format {
+        format::ALPHA8 => data.iter().flat_map(|&a| [255, 255, 255, a]).collect(),
+        format::RGB24 => data
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        format::RGBA32 => data.to_vec(),
+        format::ARGB32 => data
+            .chunks_exact(4)
+            .flat_map(|p| [p[1], p[2], p[3], p[0]])
+            .collect(),
+        format::BGRA32 => data
+            .chunks_exact(4)
+            .flat_map(|p| [p[2], p[1], p[0], p[3]])
+            .collect(),
+        format::DXT1 => decode_blocks(w, h, data, 8, |block, out| {
+            decode_color_block(block, out, true);
+        }),
+        format::DXT5 => decode_blocks(w, h, data, 16, |block, out| {
+            decode_color_block(&block[8..], out, false);
+            decode_alpha_block(&block[..8], out);
+        }),
+        _ => unreachable!("format is checked above"),
+    };
+
+    // flip rows
+    let row = w * 4;
+    for y in 0..h / 2 {
+        let (top, bottom) = pixels.split_at_mut((h - 1 - y) * row);
+        top[y * row..(y + 1) * row].swap_with_slice(&mut bottom[..row]);
+    }
+
+    Ok(Image {
+        width,
+        height,
+        pixels,
+    })
+}
+
+/// Decode 4x4 blocks, `decode` fills 16 RGBA pixels of block
+fn decode_blocks(
+    width: usize,
+    height: usize,
+    data: &[u8],
+    block_size: usize,
+    decode: impl Fn(&[u8], &mut [[u8; 4]; 16]),
+) -> Vec<u8> {
+    let mut pixels = vec![0; width * height * 4];
+    let blocks_x = width.div_ceil(4);
+    for (i, block) in data.chunks_exact(block_size).enumerate() {
+        let mut out = [[0; 4]; 16];
+        decode(block, &mut out);
+        let (bx, by) = (i % blocks_x * 4, i / blocks_x * 4);
+        for (j, pixel) in out.iter().enumerate() {
+            let (x, y) = (bx + j % 4, by + j / 4);
+            if x < width && y < height {
+                let start = (y * width + x) * 4;
+                pixels[start..start + 4].copy_from_slice(pixel);
+            }
+        }
+    }
+    pixels
+}
+
+fn rgb565(value: u16) -> [u8; 4] {
+    let r = ((value >> 11) & 0x1f) as u8;
+    let g = ((value >> 5) & 0x3f) as u8;
+    let b = (value & 0x1f) as u8;
+    [
+        (r << 3) | (r >> 2),
+        (g << 2) | (g >> 4),
+        (b << 3) | (b >> 2),
+        255,
+    ]
+}
+
+/// Decode BC1 color block. Blocks of DXT5 always use four colors
+fn decode_color_block(block: &[u8], out: &mut [[u8; 4]; 16], allow_transparent: bool) {
+    let c0 = u16::from_le_bytes([block[0], block[1]]);
+    let c1 = u16::from_le_bytes([block[2], block[3]]);
+    let (a, b) = (rgb565(c0), rgb565(c1));
+    let mix = |wa: u16, wb: u16| -> [u8; 4] {
+        let m = |i: usize| ((a[i] as u16 * wa + b[i] as u16 * wb) / (wa + wb)) as u8;
+        [m(0), m(1), m(2), 255]
+    };
+    let colors = if c0 > c1 || !allow_transparent {
+        [a, b, mix(2, 1), mix(1, 2)]
+    } else {
+        [a, b, mix(1, 1), [0, 0, 0, 0]]
+    };
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+    for (i, pixel) in out.iter_mut().enumerate() {
+        *pixel = colors[(indices >> (i * 2) & 0b11) as usize];
+    }
+}
+
+/// Decode BC3 alpha block
+fn decode_alpha_block(block: &[u8], out: &mut [[u8; 4]; 16]) {
+    let (a0, a1) = (block[0] as u16, block[1] as u16);
+    let mut alphas = [0u8; 8];
+    alphas[0] = a0 as u8;
+    alphas[1] = a1 as u8;
+    if a0 > a1 {
+        for i in 1..7 {
+            alphas[i + 1] = ((a0 * (7 - i as u16) + a1 * i as u16) / 7) as u8;
+        }
+    } else {
+        for i in 1..5 {
+            alphas[i + 1] = ((a0 * (5 - i as u16) + a1 * i as u16) / 5) as u8;
+        }
+        alphas[6] = 0;
+        alphas[7] = 255;
+    }
+    let mut indices = [0u8; 8];
+    indices[..6].copy_from_slice(&block[2..8]);
+    let indices = u64::from_le_bytes(indices);
+    for (i, pixel) in out.iter_mut().enumerate() {
+        pixel[3] = alphas[(indices >> (i * 3) & 0b111) as usize];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_rgb24_flips_rows() {
+        let data = [1, 2, 3, 4, 5, 6];
+        let image = decode(format::RGB24, 1, 2, &data).unwrap();
+        assert_eq!(image.pixels, [4, 5, 6, 255, 1, 2, 3, 255]);
+    }
+
+    #[test]
+    fn test_decode_dxt1() {
+        // red and blue, first row uses all four colors
+        let mut block = vec![];
+        block.extend_from_slice(&0xf800u16.to_le_bytes());
+        block.extend_from_slice(&0x001fu16.to_le_bytes());
+        block.extend_from_slice(&0b11_10_01_00u32.to_le_bytes());
+        let image = decode(format::DXT1, 4, 4, &block).unwrap();
+        // first row of texture is the last row of image
+        assert_eq!(
+            &image.pixels[48..],
+            [
+                255, 0, 0, 255, //
+                0, 0, 255, 255, //
+                170, 0, 85, 255, //
+                85, 0, 170, 255,
+            ]
+        );
+        assert_eq!(&image.pixels[..4], [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_decode_dxt5_alpha() {
+        let mut block = vec![255, 0];
+        // pixels use alphas 0, 1, 3 and 0 of palette
+        block.extend_from_slice(&[0b1100_1000, 0, 0, 0, 0, 0]);
+        block.extend_from_slice(&[0; 8]);
+        let image = decode(format::DXT5, 4, 4, &block).unwrap();
+        let alphas: Vec<u8> = image.pixels[48..].chunks(4).map(|p| p[3]).collect();
+        assert_eq!(alphas, [255, 0, 182, 255]);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let err = decode(format::DXT1_CRUNCHED, 4, 4, &[]).unwrap_err();
+        assert_eq!(err, TextureError::UnsupportedFormat(28));
+        assert_eq!(
+            err.to_string(),
+            "unsupported format: texture format DXT1Crunched (28)"
+        );
+        assert_eq!(
+            decode(format::RGBA32, 2, 2, &[0; 15]),
+            Err(TextureError::TooShort {
+                expected: 16,
+                actual: 15
+            })
+        );
+    }
+}
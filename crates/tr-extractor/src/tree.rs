@@ -0,0 +1,118 @@
+//! Helpers for walking tree of entries
+
+use std::collections::{BTreeMap, HashMap};
+
+use heck::ToShoutySnakeCase;
+
+use crate::models::entries::JsonEntry;
+
+/// Replace alternative rumor names by their keys. Names missing from map are
+/// converted the same way as in [`collect_rumor_alt_names`]
+pub fn replace_rumor_alt_names(entries: &mut [JsonEntry], name_to_key: &HashMap<String, String>) {
+    for e in entries {
+        for rumor in &mut e.facts.rumor {
+            if let Some(name) = &mut rumor.name {
+                *name = name_to_key
+                    .get(name)
+                    .cloned()
+                    .unwrap_or_else(|| name.to_shouty_snake_case());
+            }
+        }
+        replace_rumor_alt_names(&mut e.entries, name_to_key);
+    }
+}
+
+pub fn count_entries(entries: &[JsonEntry]) -> u32 {
+    if entries.is_empty() {
+        return 0;
+    }
+    entries.len() as u32
+        + entries
+            .iter()
+            .map(|e| count_entries(&e.entries))
+            .sum::<u32>()
+}
+
+/// Returns map of `"Rumor alt name" -> "RUMOR_ALT_NAME"`
+pub fn collect_rumor_alt_names(entries: &[JsonEntry]) -> HashMap<String, String> {
+    let mut tr = HashMap::new();
+    for e in entries {
+        for rumor in &e.facts.rumor {
+            if let Some(name) = &rumor.name {
+                tr.insert(name.to_owned(), name.to_shouty_snake_case());
+            }
+        }
+        if !e.entries.is_empty() {
+            tr.extend(collect_rumor_alt_names(&e.entries));
+        }
+    }
+    tr
+}
+
+/// Returns Vec of (text, id)
+pub fn collect_astro_texts(entries: &[JsonEntry]) -> Vec<(String, String)> {
+    let mut kvs = vec![];
+    for e in entries {
+        for fact in &e.facts.explore {
+            kvs.push((fact.text.clone(), fact.id.clone()));
+        }
+        for fact in &e.facts.rumor {
+            kvs.push((fact.text.clone(), fact.id.clone()));
+        }
+        if !e.entries.is_empty() {
+            kvs.extend_from_slice(&collect_astro_texts(&e.entries));
+        }
+    }
+    kvs
+}
+
+/// Returns Vec of (name, id)
+pub fn collect_astro_names(entries: &[JsonEntry]) -> Vec<(String, String)> {
+    let mut names = vec![];
+    for e in entries {
+        names.push((e.name.clone(), e.id.clone()));
+        if !e.entries.is_empty() {
+            names.extend_from_slice(&collect_astro_names(&e.entries));
+        }
+    }
+    names
+}
+
+/// Returns map of child entry id to parent entry id
+pub fn collect_parents(entries: &[JsonEntry]) -> BTreeMap<String, String> {
+    let mut parents = BTreeMap::new();
+    for e in entries {
+        for child in &e.entries {
+            parents.insert(child.id.clone(), e.id.clone());
+        }
+        if !e.entries.is_empty() {
+            parents.extend(collect_parents(&e.entries));
+        }
+    }
+    parents
+}
+
+/// Returns ids of all explore and rumor facts
+pub fn collect_fact_ids(entries: &[JsonEntry]) -> Vec<String> {
+    let mut ids = vec![];
+    for e in entries {
+        ids.extend(e.facts.explore.iter().map(|f| f.id.clone()));
+        ids.extend(e.facts.rumor.iter().map(|f| f.id.clone()));
+        if !e.entries.is_empty() {
+            ids.extend(collect_fact_ids(&e.entries));
+        }
+    }
+    ids
+}
+
+/// Returns ids of all entries, parents before children
+pub fn collect_entry_ids(entries: &[JsonEntry]) -> Vec<String> {
+    let mut ids = vec![];
+    for e in entries {
+        ids.push(e.id.clone());
+        if !e.entries.is_empty() {
+            ids.extend(collect_entry_ids(&e.entries));
+        }
+    }
+    ids
+}
@@ -0,0 +1,725 @@
+//! Minimal reader for Unity SerializedFile (`*.assets`, `level*`). Only parts
+//! needed for locating objects are parsed: header, type table and object
+//! table. Type trees are skipped, objects are read with known layouts
+
+use std::ops::Range;
+
+/// Class ID of `GameObject`
+pub const CLASS_GAME_OBJECT: i32 = 1;
+/// Class ID of `Texture2D`
+pub const CLASS_TEXTURE_2D: i32 = 28;
+/// Class ID of `TextAsset`
+pub const CLASS_TEXT_ASSET: i32 = 49;
+/// Class ID of `MonoBehaviour`
+pub const CLASS_MONO_BEHAVIOUR: i32 = 114;
+/// Class ID of `MonoScript`
+pub const CLASS_MONO_SCRIPT: i32 = 115;
+/// Class ID of `Sprite`
+pub const CLASS_SPRITE: i32 = 213;
+
+const MIN_SUPPORTED_VERSION: u32 = 9;
+const MAX_SUPPORTED_VERSION: u32 = 22;
+
+#[derive(Debug, thiserror::Error)]
+pub enum UnityError {
+    #[error("unexpected end of data at offset {0}")]
+    Eof(usize),
+
+    #[error("unsupported serialized file version {0}")]
+    UnsupportedVersion(u32),
+
+    #[error("invalid header: {0}")]
+    InvalidHeader(&'static str),
+
+    #[error("object data out of file bounds")]
+    OutOfBounds,
+
+    #[error("failed to convert bytes to utf-8 string: {0}")]
+    Utf8Error(#[from] std::str::Utf8Error),
+}
+
+pub type Result<T, E = UnityError> = std::result::Result<T, E>;
+
+#[derive(Debug)]
+pub struct SerializedFile<'d> {
+    data: &'d [u8],
+    big_endian: bool,
+
+    /// Format version of serialized file
+    pub version: u32,
+
+    /// Version of Unity, e.g. `2019.4.39f1`
+    pub unity_version: String,
+
+    pub objects: Vec<ObjectInfo>,
+
+    /// Paths of files, referenced by [`PPtr::file_id`]
+    pub externals: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ObjectInfo {
+    pub path_id: i64,
+    pub class_id: i32,
+
+    /// Absolute byte range of object data in file
+    pub range: Range<usize>,
+}
+
+/// Pointer to object. `file_id` 0 means current file, other values are
+/// indices in [`SerializedFile::externals`], starting from 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PPtr {
+    pub file_id: i32,
+    pub path_id: i64,
+}
+
+impl PPtr {
+    pub fn is_null(&self) -> bool {
+        self.path_id == 0
+    }
+}
+
+/// Common fields of `MonoBehaviour`
+#[derive(Debug)]
+pub struct MonoBehaviour<'d> {
+    pub game_object: PPtr,
+    pub script: PPtr,
+    pub name: &'d str,
+}
+
+/// Fields of `Sprite`, needed to get its image
+#[derive(Debug)]
+pub struct Sprite<'d> {
+    pub name: &'d str,
+    /// Rectangle in texture: x, y, width, height. Y axis points up
+    pub rect: [f32; 4],
+    /// Sprite atlas, sprite is packed into
+    pub atlas: PPtr,
+    pub texture: PPtr,
+}
+
+#[derive(Debug)]
+pub struct Texture2D<'d> {
+    pub name: &'d str,
+    pub width: u32,
+    pub height: u32,
+    /// Value of `TextureFormat` enum
+    pub format: i32,
+    pub image: ImageData<'d>,
+}
+
+/// Pixels of texture, starting from the largest mip level
+#[derive(Debug)]
+pub enum ImageData<'d> {
+    Inline(&'d [u8]),
+    /// Stored in separate resource file
+    Stream {
+        path: &'d str,
+        offset: u64,
+        size: u32,
+    },
+}
+
+#[derive(Debug)]
+pub struct TextAsset<'d> {
+    pub name: &'d str,
+    pub script: &'d [u8],
+}
+
+impl<'d> SerializedFile<'d> {
+    pub fn parse(data: &'d [u8]) -> Result<Self> {
+        // header is always big-endian
+        let mut r = Reader::new(data, true);
+        let mut metadata_size = r.u32()? as u64;
+        let mut file_size = r.u32()? as u64;
+        let version = r.u32()?;
+        let mut data_offset = r.u32()? as u64;
+
+        if !(MIN_SUPPORTED_VERSION..=MAX_SUPPORTED_VERSION).contains(&version) {
+            return Err(UnityError::UnsupportedVersion(version));
+        }
+
+        let big_endian = r.u8()? != 0;
+        r.skip(3)?;
+
+        if version >= 22 {
+            metadata_size = r.u32()? as u64;
+            file_size = r.u64()?;
+            data_offset = r.u64()?;
+            r.skip(8)?;
+        }
+        if file_size != data.len() as u64 {
+            return Err(UnityError::InvalidHeader("file size mismatch"));
+        }
+        if metadata_size > file_size || data_offset > file_size {
+            return Err(UnityError::InvalidHeader("offsets out of file bounds"));
+        }
+
+        r.big_endian = big_endian;
+        let unity_version = r.cstring()?.to_owned();
+        let _target_platform = r.i32()?;
+        let has_type_tree = if version >= 13 { r.u8()? != 0 } else { true };
+
+        let type_count = r.i32()?;
+        let mut types = Vec::new();
+        for _ in 0..type_count {
+            types.push(read_type(&mut r, version, has_type_tree)?);
+        }
+
+        if (7..14).contains(&version) {
+            let _big_id_enabled = r.i32()?;
+        }
+
+        let object_count = r.i32()?;
+        let mut objects = Vec::new();
+        for _ in 0..object_count {
+            if version >= 14 {
+                r.align(4);
+            }
+            let path_id = if version >= 14 {
+                r.i64()?
+            } else {
+                r.i32()? as i64
+            };
+            let byte_start = if version >= 22 {
+                r.i64()? as u64
+            } else {
+                r.u32()? as u64
+            };
+            let byte_size = r.u32()?;
+            let type_id = r.i32()?;
+            let mut class_id = if version < 16 {
+                r.u16()? as i32
+            } else {
+                types
+                    .get(type_id as usize)
+                    .copied()
+                    .ok_or(UnityError::InvalidHeader("unknown object type"))?
+            };
+            if version < 11 {
+                r.skip(2)?;
+            }
+            if (11..17).contains(&version) {
+                r.skip(2)?;
+            }
+            if version == 15 || version == 16 {
+                r.skip(1)?;
+            }
+            if version < 16 && class_id == 0 {
+                class_id = type_id;
+            }
+
+            let start = data_offset
+                .checked_add(byte_start)
+                .and_then(|start| usize::try_from(start).ok())
+                .ok_or(UnityError::OutOfBounds)?;
+            let end = start
+                .checked_add(byte_size as usize)
+                .filter(|end| *end <= data.len())
+                .ok_or(UnityError::OutOfBounds)?;
+            objects.push(ObjectInfo {
+                path_id,
+                class_id,
+                range: start..end,
+            });
+        }
+
+        if version >= 11 {
+            let script_count = r.i32()?.max(0) as usize;
+            for _ in 0..script_count {
+                // local file index and identifier
+                r.skip(4)?;
+                if version >= 14 {
+                    r.align(4);
+                    r.skip(8)?;
+                } else {
+                    r.skip(4)?;
+                }
+            }
+        }
+
+        let externals_count = r.i32()?.max(0) as usize;
+        let mut externals = Vec::new();
+        for _ in 0..externals_count {
+            if version >= 6 {
+                let _temp_empty = r.cstring()?;
+            }
+            if version >= 5 {
+                // guid and type
+                r.skip(20)?;
+            }
+            externals.push(r.cstring()?.to_owned());
+        }
+
+        Ok(Self {
+            data,
+            big_endian,
+            version,
+            unity_version,
+            objects,
+            externals,
+        })
+    }
+
+    /// Find object by path id
+    pub fn object(&self, path_id: i64) -> Option<&ObjectInfo> {
+        // objects are usually sorted by path id
+        match self.objects.binary_search_by_key(&path_id, |o| o.path_id) {
+            Ok(i) => Some(&self.objects[i]),
+            Err(_) => self.objects.iter().find(|o| o.path_id == path_id),
+        }
+    }
+
+    /// Name of file, referenced by pointer, without directories. Returns
+    /// `None` for current file
+    pub fn external_name(&self, pptr: PPtr) -> Option<&str> {
+        if pptr.file_id <= 0 {
+            return None;
+        }
+        let path = self.externals.get(pptr.file_id as usize - 1)?;
+        Some(path.rsplit('/').next().unwrap_or(path))
+    }
+
+    /// Iterate over objects with specified class id
+    pub fn objects_of(&self, class_id: i32) -> impl Iterator<Item = &ObjectInfo> {
+        self.objects.iter().filter(move |o| o.class_id == class_id)
+    }
+
+    /// Reader positioned at the start of object data
+    pub fn reader(&self, object: &ObjectInfo) -> Reader<'d> {
+        Reader::new(&self.data[object.range.clone()], self.big_endian)
+    }
+
+    /// Read common fields of `MonoBehaviour`. Returned reader is positioned at
+    /// fields of script
+    pub fn mono_behaviour(&self, object: &ObjectInfo) -> Result<(MonoBehaviour<'d>, Reader<'d>)> {
+        let mut r = self.reader(object);
+        let game_object = r.pptr()?;
+        let _enabled = r.u8()?;
+        r.align(4);
+        let script = r.pptr()?;
+        let name = r.aligned_str()?;
+        Ok((
+            MonoBehaviour {
+                game_object,
+                script,
+                name,
+            },
+            r,
+        ))
+    }
+
+    /// Read class name of `MonoScript`
+    pub fn mono_script_class(&self, object: &ObjectInfo) -> Result<&'d str> {
+        let mut r = self.reader(object);
+        let _name = r.aligned_str()?;
+        let _execution_order = r.i32()?;
+        // properties hash
+        r.skip(16)?;
+        r.aligned_str()
+    }
+
+    /// Read `Sprite` with layout of Unity 2019.4
+    pub fn sprite(&self, object: &ObjectInfo) -> Result<Sprite<'d>> {
+        let mut r = self.reader(object);
+        let name = r.aligned_str()?;
+        let rect = [r.f32()?, r.f32()?, r.f32()?, r.f32()?];
+        // offset, border, pixels to units, pivot, extrude
+        r.skip(8 + 16 + 4 + 8 + 4)?;
+        let _is_polygon = r.u8()?;
+        r.align(4);
+        // render data key
+        r.skip(16 + 8)?;
+        let atlas_tags = r.i32()?.max(0);
+        for _ in 0..atlas_tags {
+            r.aligned_bytes()?;
+        }
+        let atlas = r.pptr()?;
+        let texture = r.pptr()?;
+        Ok(Sprite {
+            name,
+            rect,
+            atlas,
+            texture,
+        })
+    }
+
+    /// Read `Texture2D` with layout of Unity 2019.4
+    pub fn texture_2d(&self, object: &ObjectInfo) -> Result<Texture2D<'d>> {
+        let mut r = self.reader(object);
+        let name = r.aligned_str()?;
+        let _forced_fallback_format = r.i32()?;
+        let _downscale_fallback = r.u8()?;
+        r.align(4);
+        let width = r.i32()?.max(0) as u32;
+        let height = r.i32()?.max(0) as u32;
+        let _complete_image_size = r.i32()?;
+        let format = r.i32()?;
+        let _mip_count = r.i32()?;
+        // is readable, ignore master texture limit, streaming mipmaps
+        r.skip(3)?;
+        r.align(4);
+        // streaming mipmaps priority, image count, texture dimension
+        r.skip(12)?;
+        // texture settings: filter mode, aniso, mip bias, wrap modes
+        r.skip(24)?;
+        // lightmap format, color space
+        r.skip(8)?;
+        let image = r.aligned_bytes()?;
+        let image = if image.is_empty() {
+            let offset = r.u32()? as u64;
+            let size = r.u32()?;
+            let path = r.aligned_str()?;
+            ImageData::Stream { path, offset, size }
+        } else {
+            ImageData::Inline(image)
+        };
+        Ok(Texture2D {
+            name,
+            width,
+            height,
+            format,
+            image,
+        })
+    }
+
+    pub fn text_assets(&self) -> impl Iterator<Item = Result<TextAsset<'d>>> {
+        self.objects_of(CLASS_TEXT_ASSET).map(|o| {
+            let mut r = self.reader(o);
+            Ok(TextAsset {
+                name: r.aligned_str()?,
+                script: r.aligned_bytes()?,
+            })
+        })
+    }
+}
+
+/// Read type entry and return its class id
+fn read_type(r: &mut Reader, version: u32, has_type_tree: bool) -> Result<i32> {
+    let class_id = r.i32()?;
+    if version >= 16 {
+        let _is_stripped = r.u8()?;
+    }
+    if version >= 17 {
+        let _script_type_index = r.i16()?;
+    }
+    if version >= 13 {
+        if (version < 16 && class_id < 0) || (version >= 16 && class_id == 114) {
+            // script id
+            r.skip(16)?;
+        }
+        // old type hash
+        r.skip(16)?;
+    }
+    if has_type_tree {
+        if version < 12 && version != 10 {
+            return Err(UnityError::UnsupportedVersion(version));
+        }
+        let node_count = r.i32()?.max(0) as usize;
+        let strings_size = r.i32()?.max(0) as usize;
+        let node_size = if version >= 19 { 32 } else { 24 };
+        r.skip(node_count * node_size + strings_size)?;
+        if version >= 21 {
+            let deps = r.i32()?.max(0) as usize;
+            r.skip(deps * 4)?;
+        }
+    }
+    Ok(class_id)
+}
+
+/// Cursor over bytes of serialized file
+#[derive(Debug, Clone)]
+pub struct Reader<'d> {
+    data: &'d [u8],
+    pos: usize,
+    big_endian: bool,
+}
+
+macro_rules! read_num {
+    ($name:ident, $ty:ty) => {
+        pub fn $name(&mut self) -> Result<$ty> {
+            let bytes = self.array()?;
+            Ok(if self.big_endian {
+                <$ty>::from_be_bytes(bytes)
+            } else {
+                <$ty>::from_le_bytes(bytes)
+            })
+        }
+    };
+}
+
+impl<'d> Reader<'d> {
+    pub fn new(data: &'d [u8], big_endian: bool) -> Self {
+        Self {
+            data,
+            pos: 0,
+            big_endian,
+        }
+    }
+
+    read_num!(u16, u16);
+    read_num!(i16, i16);
+    read_num!(u32, u32);
+    read_num!(i32, i32);
+    read_num!(u64, u64);
+    read_num!(i64, i64);
+    read_num!(f32, f32);
+
+    pub fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub fn bytes(&mut self, len: usize) -> Result<&'d [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.data.len())
+            .ok_or(UnityError::Eof(self.pos))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.bytes(N)?.try_into().expect("slice has correct length"))
+    }
+
+    pub fn skip(&mut self, len: usize) -> Result<()> {
+        self.bytes(len).map(|_| ())
+    }
+
+    pub fn align(&mut self, to: usize) {
+        self.pos = self.pos.next_multiple_of(to).min(self.data.len());
+    }
+
+    pub fn pptr(&mut self) -> Result<PPtr> {
+        Ok(PPtr {
+            file_id: self.i32()?,
+            path_id: self.i64()?,
+        })
+    }
+
+    /// Null-terminated string
+    pub fn cstring(&mut self) -> Result<&'d str> {
+        let rest = &self.data[self.pos..];
+        let len = rest
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or(UnityError::Eof(self.data.len()))?;
+        let s = std::str::from_utf8(&rest[..len])?;
+        self.pos += len + 1;
+        Ok(s)
+    }
+
+    /// Length-prefixed byte array, aligned to 4 bytes after reading
+    pub fn aligned_bytes(&mut self) -> Result<&'d [u8]> {
+        let len = self.i32()?;
+        if len < 0 {
+            return Err(UnityError::Eof(self.pos));
+        }
+        let bytes = self.bytes(len as usize)?;
+        self.align(4);
+        Ok(bytes)
+    }
+
+    /// Length-prefixed string, aligned to 4 bytes after reading
+    pub fn aligned_str(&mut self) -> Result<&'d str> {
+        Ok(std::str::from_utf8(self.aligned_bytes()?)?)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Build minimal serialized file of version 21 (little-endian, without
+    /// type trees) with given objects
+    pub(crate) fn build_file(objects: &[(i32, Vec<u8>)]) -> Vec<u8> {
+        let mut class_ids: Vec<i32> = vec![];
+        for (c, _) in objects {
+            if !class_ids.contains(c) {
+                class_ids.push(*c);
+            }
+        }
+
+        let mut meta = vec![];
+        meta.extend_from_slice(b"2019.4.39f1\0");
+        meta.extend_from_slice(&19i32.to_le_bytes());
+        meta.push(0);
+        meta.extend_from_slice(&(class_ids.len() as i32).to_le_bytes());
+        for c in &class_ids {
+            meta.extend_from_slice(&c.to_le_bytes());
+            meta.push(0);
+            meta.extend_from_slice(&(-1i16).to_le_bytes());
+            if *c == 114 {
+                meta.extend_from_slice(&[0; 16]);
+            }
+            meta.extend_from_slice(&[0; 16]);
+        }
+
+        const HEADER_LEN: usize = 20;
+        let mut data = vec![];
+        let mut objects_meta = vec![];
+        objects_meta.extend_from_slice(&(objects.len() as i32).to_le_bytes());
+        for (i, (class_id, bytes)) in objects.iter().enumerate() {
+            while !(HEADER_LEN + meta.len() + objects_meta.len()).is_multiple_of(4) {
+                objects_meta.push(0);
+            }
+            let type_id = class_ids.iter().position(|c| c == class_id).unwrap();
+            objects_meta.extend_from_slice(&(i as i64 + 1).to_le_bytes());
+            objects_meta.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            objects_meta.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+            objects_meta.extend_from_slice(&(type_id as i32).to_le_bytes());
+            data.extend_from_slice(bytes);
+            while !data.len().is_multiple_of(8) {
+                data.push(0);
+            }
+        }
+        meta.extend(objects_meta);
+        // script types and externals
+        meta.extend_from_slice(&0i32.to_le_bytes());
+        meta.extend_from_slice(&0i32.to_le_bytes());
+
+        let data_offset = (HEADER_LEN + meta.len()).next_multiple_of(16);
+        let file_size = data_offset + data.len();
+        let mut file = vec![];
+        file.extend_from_slice(&(meta.len() as u32).to_be_bytes());
+        file.extend_from_slice(&(file_size as u32).to_be_bytes());
+        file.extend_from_slice(&21u32.to_be_bytes());
+        file.extend_from_slice(&(data_offset as u32).to_be_bytes());
+        file.extend_from_slice(&[0; 4]);
+        file.extend(meta);
+        file.resize(data_offset, 0);
+        file.extend(data);
+        file
+    }
+
+    pub(crate) fn aligned(bytes: &[u8]) -> Vec<u8> {
+        let mut out = (bytes.len() as i32).to_le_bytes().to_vec();
+        out.extend_from_slice(bytes);
+        while !out.len().is_multiple_of(4) {
+            out.push(0);
+        }
+        out
+    }
+
+    pub(crate) fn text_asset(name: &str, script: &str) -> Vec<u8> {
+        let mut out = aligned(name.as_bytes());
+        out.extend(aligned(script.as_bytes()));
+        out
+    }
+
+    pub(crate) fn mono_script(class: &str) -> Vec<u8> {
+        let mut out = aligned(class.as_bytes());
+        out.extend_from_slice(&0i32.to_le_bytes());
+        out.extend_from_slice(&[0; 16]);
+        out.extend(aligned(class.as_bytes()));
+        out.extend(aligned(b""));
+        out.extend(aligned(b"Assembly-CSharp.dll"));
+        out
+    }
+
+    /// `MonoBehaviour` with script from the same file, followed by `fields`
+    pub(crate) fn mono_behaviour(script_path_id: i64, name: &str, fields: &[u8]) -> Vec<u8> {
+        let mut out = vec![];
+        out.extend_from_slice(&0i32.to_le_bytes());
+        out.extend_from_slice(&0i64.to_le_bytes());
+        out.extend_from_slice(&[1, 0, 0, 0]);
+        out.extend_from_slice(&0i32.to_le_bytes());
+        out.extend_from_slice(&script_path_id.to_le_bytes());
+        out.extend(aligned(name.as_bytes()));
+        out.extend_from_slice(fields);
+        out
+    }
+
+    pub(crate) fn sprite(name: &str, texture_path_id: i64, rect: [f32; 4]) -> Vec<u8> {
+        let mut out = aligned(name.as_bytes());
+        for v in rect {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        out.extend_from_slice(&[0; 40]);
+        out.extend_from_slice(&[0; 4]);
+        out.extend_from_slice(&[0; 24]);
+        // atlas tags and atlas
+        out.extend_from_slice(&0i32.to_le_bytes());
+        out.extend_from_slice(&[0; 12]);
+        out.extend_from_slice(&0i32.to_le_bytes());
+        out.extend_from_slice(&texture_path_id.to_le_bytes());
+        out
+    }
+
+    pub(crate) fn texture_2d(
+        name: &str,
+        width: i32,
+        height: i32,
+        format: i32,
+        pixels: &[u8],
+    ) -> Vec<u8> {
+        let mut out = aligned(name.as_bytes());
+        out.extend_from_slice(&0i32.to_le_bytes());
+        out.extend_from_slice(&[0; 4]);
+        for v in [width, height, pixels.len() as i32, format, 1] {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        out.extend_from_slice(&[0; 4]);
+        out.extend_from_slice(&[0; 12 + 24 + 8]);
+        out.extend(aligned(pixels));
+        out
+    }
+
+    #[test]
+    fn test_parse_mono_behaviour() {
+        let file = build_file(&[
+            (CLASS_MONO_SCRIPT, mono_script("ShipLogLibrary")),
+            (
+                CLASS_MONO_BEHAVIOUR,
+                mono_behaviour(1, "Library", &[7, 0, 0, 0]),
+            ),
+        ]);
+        let parsed = SerializedFile::parse(&file).unwrap();
+        assert!(parsed.externals.is_empty());
+
+        let script = parsed.object(1).unwrap();
+        assert_eq!(parsed.mono_script_class(script).unwrap(), "ShipLogLibrary");
+
+        let (behaviour, mut r) = parsed.mono_behaviour(parsed.object(2).unwrap()).unwrap();
+        assert_eq!(behaviour.name, "Library");
+        assert_eq!(
+            behaviour.script,
+            PPtr {
+                file_id: 0,
+                path_id: 1
+            }
+        );
+        assert_eq!(parsed.external_name(behaviour.script), None);
+        assert_eq!(r.i32().unwrap(), 7);
+    }
+
+    #[test]
+    fn test_parse_text_assets() {
+        let file = build_file(&[
+            (CLASS_TEXT_ASSET, text_asset("TranslationTable_XML", "<a/>")),
+            (1, vec![1, 2, 3]),
+            (CLASS_TEXT_ASSET, text_asset("Ship", "<AstroObjectEntry/>")),
+        ]);
+
+        let parsed = SerializedFile::parse(&file).unwrap();
+        assert_eq!(parsed.version, 21);
+        assert_eq!(parsed.unity_version, "2019.4.39f1");
+        assert_eq!(parsed.objects.len(), 3);
+
+        let assets = parsed
+            .text_assets()
+            .map(|a| a.map(|a| (a.name, a.script)))
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            assets,
+            vec![
+                ("TranslationTable_XML", b"<a/>".as_slice()),
+                ("Ship", b"<AstroObjectEntry/>".as_slice()),
+            ]
+        );
+    }
+}
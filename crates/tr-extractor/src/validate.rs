@@ -0,0 +1,48 @@
+//! Checks of extracted data
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use anyhow::Result;
+use ow_save::{Save, SaveKeysDiff};
+
+use crate::models::entries::JsonEntry;
+
+/// Compare extracted save keys with keys of `shipLogFactSaves` in game save
+pub fn compare_save_keys(keys: &BTreeSet<String>, save: &Path) -> Result<SaveKeysDiff> {
+    Ok(Save::open(save)?.compare_keys(keys))
+}
+
+/// Returns ids of entries and facts, which have no translation
+pub fn missing_translations(entries: &[JsonEntry], tr: &BTreeMap<String, String>) -> Vec<String> {
+    let mut missing = vec![];
+    collect_missing(entries, tr, &mut missing);
+    missing
+}
+
+fn collect_missing(
+    entries: &[JsonEntry],
+    tr: &BTreeMap<String, String>,
+    missing: &mut Vec<String>,
+) {
+    for e in entries {
+        if tr.get(&e.id).is_none() {
+            missing.push(e.id.clone());
+        }
+        for f in &e.facts.explore {
+            if tr.get(&f.id).is_none() {
+                missing.push(f.id.clone());
+            }
+        }
+        for f in &e.facts.rumor {
+            if tr.get(&f.id).is_none() {
+                missing.push(f.id.clone());
+            }
+        }
+        if !e.entries.is_empty() {
+            collect_missing(&e.entries, tr, missing);
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! Minimal parser of Valve's KeyValues text format, used by Steam for
+//! `libraryfolders.vdf` and app manifests
+
+/// Value of key. Keys are kept in order of appearance, duplicate keys are
+/// allowed
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Str(String),
+    Map(Vec<(String, Value)>),
+}
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum VdfError {
+    #[error("unexpected end of file")]
+    Eof,
+
+    #[error("unexpected {0:?} at line {1}")]
+    Unexpected(char, usize),
+}
+
+impl Value {
+    /// Get value by key, case-insensitive like in Steam
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Map(items) => items
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, v)| v),
+            Value::Str(_) => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Str(s) => Some(s),
+            Value::Map(_) => None,
+        }
+    }
+
+    pub fn entries(&self) -> &[(String, Value)] {
+        match self {
+            Value::Map(items) => items,
+            Value::Str(_) => &[],
+        }
+    }
+}
+
+/// Parse document. Returns map of top-level keys
+pub fn parse(data: &str) -> Result<Value, VdfError> {
+    let mut tokens = Tokens {
+        chars: data.chars().peekable(),
+        line: 1,
+    };
+    let items = parse_map(&mut tokens, false)?;
+    Ok(Value::Map(items))
+}
+
+enum Token {
+    Str(String),
+    Open,
+    Close,
+}
+
+struct Tokens<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+}
+
+impl Tokens<'_> {
+    fn next(&mut self) -> Result<Option<Token>, VdfError> {
+        loop {
+            let Some(ch) = self.chars.next() else {
+                return Ok(None);
+            };
+            match ch {
+                '\n' => self.line += 1,
+                ch if ch.is_whitespace() => {}
+                '/' if self.chars.peek() == Some(&'/') => {
+                    for ch in self.chars.by_ref() {
+                        if ch == '\n' {
+                            self.line += 1;
+                            break;
+                        }
+                    }
+                }
+                '{' => return Ok(Some(Token::Open)),
+                '}' => return Ok(Some(Token::Close)),
+                '"' => return self.quoted().map(|s| Some(Token::Str(s))),
+                ch => return Err(VdfError::Unexpected(ch, self.line)),
+            }
+        }
+    }
+
+    fn quoted(&mut self) -> Result<String, VdfError> {
+        let mut s = String::new();
+        loop {
+            match self.chars.next().ok_or(VdfError::Eof)? {
+                '"' => return Ok(s),
+                '\\' => match self.chars.next().ok_or(VdfError::Eof)? {
+                    'n' => s.push('\n'),
+                    't' => s.push('\t'),
+                    ch => s.push(ch),
+                },
+                '\n' => {
+                    self.line += 1;
+                    s.push('\n');
+                }
+                ch => s.push(ch),
+            }
+        }
+    }
+}
+
+fn parse_map(tokens: &mut Tokens, nested: bool) -> Result<Vec<(String, Value)>, VdfError> {
+    let mut items = vec![];
+    loop {
+        let key = match tokens.next()? {
+            Some(Token::Str(key)) => key,
+            Some(Token::Close) if nested => return Ok(items),
+            Some(Token::Open) => return Err(VdfError::Unexpected('{', tokens.line)),
+            Some(Token::Close) => return Err(VdfError::Unexpected('}', tokens.line)),
+            None if nested => return Err(VdfError::Eof),
+            None => return Ok(items),
+        };
+        let value = match tokens.next()?.ok_or(VdfError::Eof)? {
+            Token::Str(value) => Value::Str(value),
+            Token::Open => Value::Map(parse_map(tokens, true)?),
+            Token::Close => return Err(VdfError::Unexpected('}', tokens.line)),
+        };
+        items.push((key, value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let data = r#"
+// comment
+"libraryfolders"
+{
+	"0"
+	{
+		"path"		"/home/user/.local/share/Steam"
+		"apps"
+		{
+			"753640"		"8345225012"
+		}
+	}
+	"1"		"D:\\Games"
+}
+"#;
+        let value = parse(data).unwrap();
+        let folders = value.get("LibraryFolders").unwrap();
+        assert_eq!(
+            folders.get("0").and_then(|f| f.get("path")).unwrap(),
+            &Value::Str("/home/user/.local/share/Steam".to_string())
+        );
+        assert_eq!(folders.get("1").and_then(Value::as_str), Some("D:\\Games"));
+        assert_eq!(folders.entries().len(), 2);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse("\"a\" {"), Err(VdfError::Eof));
+        assert_eq!(parse("\"a\"\n}"), Err(VdfError::Unexpected('}', 2)));
+        assert_eq!(parse("a"), Err(VdfError::Unexpected('a', 1)));
+    }
+}
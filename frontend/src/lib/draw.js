@@ -21,7 +21,11 @@ import {
 } from "@/lib/data";
 import { detect_language } from "@/lib/language";
 import { coord_to_leaflet } from "@/lib/leaflet";
-import { get_save_from_browser_url, has_save_in_url } from "@/lib/saves";
+import {
+	get_save_from_browser_url,
+	has_save_in_url,
+	load_meta,
+} from "@/lib/saves";
 import {
 	LOADING,
 	LOADING_TOTAL,
@@ -66,15 +70,19 @@ function flatten_entries(entries, result) {
 }
 
 export async function generate_all_svg() {
+	// number of fetches before fetching images
+	LOADING_TOTAL.set(5);
+	LOADING.set(0);
+
+	// save length in url depends on number of keys
+	await load_meta();
+	LOADING.update((n) => n + 1);
+
 	let save_loaded = has_save_in_url();
 	SAVE_FOUND.set(save_loaded);
 
 	let consider_ignored = get(SETTINGS).consider_ignored_facts;
 
-	// number of fetches before fetching images
-	LOADING_TOTAL.set(4);
-	LOADING.set(0);
-
 	let save_keys = await (
 		await fetch(import.meta.env.BASE_URL + "/save_keys.json")
 	).json();
@@ -1,9 +1,21 @@
-const V15_KEYS_COUNT = 374;
-const V16_KEYS_COUNT = 374;
-export const KEYS_COUNT = V16_KEYS_COUNT;
-const GAME_VERSION = "1.1.16";
+// game version and number of save keys, read from meta.json written by
+// tr-extractor
+let game_version;
+export let KEYS_COUNT;
 const ENCODING_VERSION = 1;
-const ENCODED_SAVE_LEN = 64;
+
+export async function load_meta() {
+	let meta = await (
+		await fetch(import.meta.env.BASE_URL + "/meta.json")
+	).json();
+	game_version = meta.game_version;
+	KEYS_COUNT = meta.save_keys_count;
+}
+
+// bools are packed to bytes, which are encoded with base64
+function encoded_save_len() {
+	return Math.ceil(Math.ceil(KEYS_COUNT / 8) / 3) * 4;
+}
 
 export function get_save_opened_facts(facts_data) {
 	// todo: not sure if read and newlyRevealed affect showing
@@ -29,7 +41,7 @@ export function get_save_opened_facts(facts_data) {
 export function export_save_to_browser_url(keys, opened) {
 	let encoded = encode_save(keys, opened);
 	// save version for now
-	window.location.hash = `v=${GAME_VERSION}&ev=${ENCODING_VERSION}&save=${encoded}`;
+	window.location.hash = `v=${game_version}&ev=${ENCODING_VERSION}&save=${encoded}`;
 }
 
 export function get_save_from_browser_url(keys) {
@@ -46,7 +58,8 @@ export function get_save_from_browser_url(keys) {
 export function has_save_in_url() {
 	let h = window.location.hash;
 	return (
-		h.includes("save=") && h.split("save=")[1].length == ENCODED_SAVE_LEN
+		h.includes("save=") &&
+		h.split("save=")[1].length == encoded_save_len()
 	);
 }
 